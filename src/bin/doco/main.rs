@@ -1,25 +1,72 @@
 extern crate doco;
 
 use std::env;
+use std::error::Error;
 use std::process;
 
 use doco::daikon::invariants;
+use doco::jpf::driver::Driver;
 use doco::Config;
 
 fn usage(program_name: &str) {
     eprintln!(
-        "Usage: {} <json config>|<path/to/config.json> <package> <class> <method signature> <tester-class>",
+        "Usage: {} <json config>|<path/to/config.json> <package> <class> <method signature> <tester-class> [<assumed condition>...]",
         program_name
     );
     process::exit(1);
 }
 
+/// Prints the documentation inferred by JPF for every method it explored, in
+/// terms of the parameters of the original method if JPF explored a driver.
+fn print_jpf_output(
+    config: &Config,
+    driver: Option<&Driver>,
+    out_json_path: &str,
+    depth: u32,
+) -> Result<(), Box<Error>> {
+    let rename = |s: &str| driver.map_or_else(|| String::from(s), |driver| driver.rename(s));
+    let preconditions = doco::jpf::process_output(out_json_path)?;
+    let safe = doco::jpf::safe_precondition(out_json_path)?;
+    let returns = doco::jpf::returns(out_json_path)?;
+    let throws = doco::jpf::throws(out_json_path)?;
+    let partial = doco::jpf::partial(depth, out_json_path)?;
+    // the examples would list the arguments of the driver
+    let examples = if config.show_examples() && driver.is_none() {
+        doco::jpf::examples(out_json_path)?
+    } else {
+        Default::default()
+    };
+    for (method, precondition) in preconditions.iter() {
+        // the results only cover the paths JDart finished exploring
+        let note = match partial[method] {
+            Some(ref note) => format!(" {}", note),
+            None => String::new(),
+        };
+        // the original method rather than the driver
+        let name = driver.map_or(method, |driver| &driver.signature);
+        println!("#doco-jpf {}: {}{}", name, rename(precondition), note);
+        println!("#doco-jpf-safe {}: {}{}", name, rename(&safe[method]), note);
+        if returns[method].len() != 0 {
+            println!("#doco-jpf-returns {}: {}{}", name, rename(&returns[method]), note);
+        }
+        for s in throws[method].iter() {
+            println!("#doco-jpf-throws {}: {}{}", name, rename(s), note);
+        }
+        if let Some(examples) = examples.get(method) {
+            if examples.len() != 0 {
+                println!("#doco-jpf-examples {}: e.g. {}", name, examples.join(", "));
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn main() {
     let args: Vec<String> = ::std::env::args().collect();
-    if args.len() != 6 {
+    if args.len() < 6 {
         usage(&args[0]);
     }
-    let config = {
+    let mut config = {
         let content = if args[1].ends_with(".json") {
             // reading from file
             doco::read_file_to_string(&args[1]).unwrap_or_else(|e| {
@@ -36,14 +83,51 @@ pub fn main() {
         })
     };
 
+    // the remaining arguments are conditions JDart may assume
+    config.add_constraints(&args[6..]);
+
     let output_path = doco::create_random_path(&env::temp_dir(), "doco", 28).unwrap_or_else(|e| {
         eprintln!("Unable to create output dir, err = {}", e);
         process::exit(1);
     });
 
+    // the results only hold for inputs satisfying the assumptions
+    let assumptions = doco::jpf::assumptions(&config, &args[2], &args[3], &args[4])
+        .unwrap_or_else(|e| {
+            eprintln!("Unable to find the assumptions, err = {}", e.description());
+            Vec::new()
+        });
+    if assumptions.len() != 0 {
+        println!("#doco-jpf-assuming {}", assumptions.join(" && "));
+    }
+
+    // JDart cannot make objects symbolic, so a driver takes their fields instead
+    let driver = Driver::generate(&config, &output_path, &args[2], &args[3], &args[4])
+        .and_then(|driver| match driver {
+            Some(driver) => driver.compile(&config).map(|()| Some(driver)),
+            None => Ok(None),
+        })
+        .unwrap_or_else(|e| {
+            eprintln!("Unable to generate a driver, err = {}", e.description());
+            None
+        });
+    if let Some(ref driver) = driver {
+        eprintln!("Exploring the driver {}.{}", driver.class, driver.method);
+        config.add_classpath(&driver.classpath);
+    }
+    let (class, method) = match driver {
+        Some(ref driver) => (driver.class.as_str(), driver.method.as_str()),
+        None => (args[3].as_str(), args[4].as_str()),
+    };
+    // JDart assumes the conditions on the parameters of the driver
+    let constraints: Vec<String> = match driver {
+        Some(ref driver) => assumptions.iter().map(|c| driver.encode(c)).collect(),
+        None => assumptions,
+    };
+
     // construct the environment for JPF
     let (out_json_path, mut jpfcmd) =
-        doco::jpf::setup_environment(&config, &output_path, &args[2], &args[3], &args[4])
+        doco::jpf::setup_environment(&config, &output_path, &args[2], class, method, &constraints)
             .unwrap_or_else(|e| {
                 eprintln!("Unable to setup JPF environment, err = {}", e.description());
                 process::exit(1);
@@ -62,11 +146,16 @@ pub fn main() {
         );
     eprintln!("Daikon output to: {}", out_inv_path);
 
-    eprintln!("Spawning JPF");
-    let mut jpf = jpfcmd.spawn().unwrap_or_else(|e| {
-        eprintln!("Unable to execute JPF, err = {}", e);
-        process::exit(1);
-    });
+    // with a time budget, JPF is rerun with increasing depths later on
+    let jpf = if config.time_budget().is_none() {
+        eprintln!("Spawning JPF");
+        Some(jpfcmd.spawn().unwrap_or_else(|e| {
+            eprintln!("Unable to execute JPF, err = {}", e);
+            process::exit(1);
+        }))
+    } else {
+        None
+    };
 
     eprintln!("Spawning Daikon instrumentation and inference engine");
     let mut dyncomp = dyncompcmd.spawn().unwrap_or_else(|e| {
@@ -74,12 +163,51 @@ pub fn main() {
         process::exit(1);
     });
 
-    match jpf.wait() {
-        Ok(status) if status.success() => match doco::jpf::process_output(&out_json_path) {
-            Ok(s) => println!("#doco-jpf {}", s),
-            Err(e) => eprintln!("Error: {}", e.description()),
+    match (jpf, config.time_budget()) {
+        (Some(mut jpf), _) => match jpf.wait() {
+            Ok(status) if status.success() => {
+                let result = doco::jpf::max_depth(&config, &args[2], class, method)
+                    .and_then(|depth| {
+                        print_jpf_output(&config, driver.as_ref(), &out_json_path, depth)
+                    });
+                if let Err(e) = result {
+                    eprintln!("Error: {}", e.description());
+                }
+            }
+            _ => eprintln!("JPF exited with an error"),
         },
-        _ => eprintln!("JPF exited with an error"),
+        (None, Some(budget)) => {
+            eprintln!("Running JPF with increasing depths");
+            let result = doco::jpf::deepen(
+                &config,
+                &output_path,
+                &args[2],
+                class,
+                method,
+                &constraints,
+                budget,
+            ).and_then(|deepening| {
+                println!(
+                    "#doco-jpf-depth {}{}",
+                    deepening.depth,
+                    if deepening.converged {
+                        " (converged)"
+                    } else {
+                        " (time budget spent before converging)"
+                    }
+                );
+                print_jpf_output(
+                    &config,
+                    driver.as_ref(),
+                    &deepening.out_json_path,
+                    deepening.depth,
+                )
+            });
+            if let Err(e) = result {
+                eprintln!("Error: {}", e.description());
+            }
+        }
+        (None, None) => unreachable!(),
     }
 
     match dyncomp.wait() {
@@ -0,0 +1,411 @@
+use std::cmp;
+
+/// A difference-bound matrix, i.e. a conjunction of constraints of the form
+/// `x - y <= c`, `x <= c` and `x >= c` over integer variables.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Dbm {
+    names: Vec<String>,
+    /// bounds[i][j] is the upper bound of x_i - x_j, or None if there is
+    /// none. x_0 is the constant 0 and x_i the variable names[i - 1]. The
+    /// bounds are kept closed, i.e. as tight as the constraints imply.
+    bounds: Vec<Vec<Option<i128>>>,
+    /// the pairs (i, j) of variables whose differences the source states with
+    /// x_i on the left-hand side, e.g. `i > j` rather than `j < i`
+    orientations: Vec<(usize, usize)>,
+}
+
+fn min(a: Option<i128>, b: Option<i128>) -> Option<i128> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(cmp::min(a, b)),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+fn max(a: Option<i128>, b: Option<i128>) -> Option<i128> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(cmp::max(a, b)),
+        _ => None,
+    }
+}
+
+fn add(a: Option<i128>, b: Option<i128>) -> Option<i128> {
+    // an overflowing sum is too large to be a useful bound
+    a?.checked_add(b?)
+}
+
+/// Whether the bound `a` is at least as tight as `b`.
+fn tighter(a: Option<i128>, b: Option<i128>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a <= b,
+        (_, None) => true,
+        (None, Some(_)) => false,
+    }
+}
+
+/// Tightens the bounds along all paths, using the Floyd-Warshall algorithm.
+fn close(bounds: &mut Vec<Vec<Option<i128>>>) {
+    let n = bounds.len();
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
+                let through = add(bounds[i][k], bounds[k][j]);
+                if !tighter(bounds[i][j], through) {
+                    bounds[i][j] = through;
+                }
+            }
+        }
+    }
+}
+
+impl Dbm {
+    /// The matrix without any constraint on `names`.
+    pub fn new(names: Vec<String>) -> Dbm {
+        let n = names.len() + 1;
+        let mut bounds = vec![vec![None; n]; n];
+        for i in 0..n {
+            bounds[i][i] = Some(0);
+        }
+        Dbm {
+            names,
+            bounds,
+            orientations: Vec::new(),
+        }
+    }
+
+    pub fn get_names(&self) -> &Vec<String> {
+        &self.names
+    }
+
+    fn index(&self, name: Option<&str>) -> usize {
+        match name {
+            Some(name) => {
+                1 + self.names
+                    .iter()
+                    .position(|n| n == name)
+                    .expect("unknown variable")
+            }
+            None => 0,
+        }
+    }
+
+    /// The upper bound of `x - y`, where None stands for the constant 0.
+    pub fn bound(&self, x: Option<&str>, y: Option<&str>) -> Option<i128> {
+        self.bounds[self.index(x)][self.index(y)]
+    }
+
+    /// Adds the constraint `x - y <= c`, where None stands for the constant 0,
+    /// e.g. `constrain(None, Some("a"), -2)` for `a >= 2`.
+    pub fn constrain(&mut self, x: Option<&str>, y: Option<&str>, c: i128) {
+        let (i, j) = (self.index(x), self.index(y));
+        if tighter(self.bounds[i][j], Some(c)) {
+            return;
+        }
+        self.bounds[i][j] = Some(c);
+        close(&mut self.bounds);
+    }
+
+    /// States the differences of `x` and `y` with `x` on the left-hand side,
+    /// as the source does.
+    pub fn orient(&mut self, x: &str, y: &str) {
+        let (i, j) = (self.index(Some(x)), self.index(Some(y)));
+        if !self.orientations.contains(&(i, j)) {
+            self.orientations.push((i, j));
+        }
+    }
+
+    /// Whether the constraints contradict each other.
+    pub fn is_empty(&self) -> bool {
+        (0..self.bounds.len()).any(|i| self.bounds[i][i].map_or(false, |c| c < 0))
+    }
+
+    /// Whether every assignment satisfying `other` satisfies this matrix.
+    pub fn includes(&self, other: &Dbm) -> bool {
+        assert_eq!(self.names, other.names);
+        if other.is_empty() {
+            return true;
+        }
+        if self.is_empty() {
+            return false;
+        }
+        self.bounds
+            .iter()
+            .zip(other.bounds.iter())
+            .all(|(a, b)| a.iter().zip(b.iter()).all(|(&a, &b)| tighter(b, a)))
+    }
+
+    /// The tightest matrix including both, which may also include assignments
+    /// satisfying neither.
+    pub fn join(&self, other: &Dbm) -> Dbm {
+        assert_eq!(self.names, other.names);
+        if self.is_empty() {
+            return other.clone();
+        }
+        if other.is_empty() {
+            return self.clone();
+        }
+        let mut orientations = self.orientations.clone();
+        for orientation in other.orientations.iter() {
+            if !orientations.contains(orientation) {
+                orientations.push(*orientation);
+            }
+        }
+        Dbm {
+            names: self.names.clone(),
+            bounds: self.bounds
+                .iter()
+                .zip(other.bounds.iter())
+                .map(|(a, b)| a.iter().zip(b.iter()).map(|(&a, &b)| max(a, b)).collect())
+                .collect(),
+            orientations,
+        }
+    }
+
+    pub fn meet(&self, other: &Dbm) -> Dbm {
+        assert_eq!(self.names, other.names);
+        let mut bounds: Vec<Vec<Option<i128>>> = self.bounds
+            .iter()
+            .zip(other.bounds.iter())
+            .map(|(a, b)| a.iter().zip(b.iter()).map(|(&a, &b)| min(a, b)).collect())
+            .collect();
+        close(&mut bounds);
+        Dbm {
+            names: self.names.clone(),
+            bounds,
+            orientations: self.orientations.clone(),
+        }
+    }
+
+    /// The join of both if it holds exactly when either does, i.e. if one
+    /// includes the other or they differ in a single constraint one states
+    /// and the other negates, e.g. `a < b && c > 0` and `a >= b && c > 0`.
+    pub fn exact_join(&self, other: &Dbm) -> Option<Dbm> {
+        if self.includes(other) {
+            return Some(self.clone());
+        }
+        if other.includes(self) {
+            return Some(other.clone());
+        }
+        let joined = self.join(other);
+        // the join satisfying a constraint of one lies in that one, and the
+        // join violating it in the other
+        let splits = |a: &Dbm, b: &Dbm| {
+            a.minimal_constraints().into_iter().any(|(i, j, c)| {
+                let mut constraint = Dbm::new(a.names.clone());
+                constraint.bounds[i][j] = Some(c);
+                close(&mut constraint.bounds);
+                let complement = constraint.complement().unwrap();
+                a.includes(&joined.meet(&constraint)) && b.includes(&joined.meet(&complement))
+            })
+        };
+        if splits(self, other) || splits(other, self) {
+            Some(joined)
+        } else {
+            None
+        }
+    }
+
+    /// The assignments not satisfying this matrix, if they form a matrix
+    /// themselves, i.e. if there is at most a single constraint.
+    pub fn complement(&self) -> Option<Dbm> {
+        let mut complement = Dbm::new(self.names.clone());
+        if self.is_empty() {
+            return Some(complement);
+        }
+        match self.minimal_constraints().as_slice() {
+            // not x_i - x_j <= c  <=>  x_j - x_i <= -c - 1
+            &[(i, j, c)] => {
+                complement.bounds[j][i] = Some(-c - 1);
+                close(&mut complement.bounds);
+                Some(complement)
+            }
+            _ => None,
+        }
+    }
+
+    /// The constraints `(i, j, c)` for `x_i - x_j <= c` that are not implied
+    /// by the others. Bounds on single variables are kept in favour of the
+    /// differences they imply.
+    fn minimal_constraints(&self) -> Vec<(usize, usize, i128)> {
+        let n = self.bounds.len();
+        let mut kept = vec![vec![None; n]; n];
+        let mut candidates = Vec::new();
+        for i in 0..n {
+            kept[i][i] = Some(0);
+            for j in 0..n {
+                if let (false, Some(c)) = (i == j, self.bounds[i][j]) {
+                    kept[i][j] = Some(c);
+                    candidates.push((i, j, c));
+                }
+            }
+        }
+        // try to drop differences before bounds
+        candidates.sort_by_key(|&(i, j, _)| i == 0 || j == 0);
+        let mut constraints = Vec::new();
+        for (i, j, c) in candidates.into_iter() {
+            let mut without = kept.clone();
+            without[i][j] = None;
+            close(&mut without);
+            if tighter(without[i][j], Some(c)) {
+                kept[i][j] = None;
+            } else {
+                constraints.push((i, j, c));
+            }
+        }
+        constraints.sort();
+        constraints
+    }
+
+    /// The constraints as Java conditions, e.g. `a <= 1` or `a < b`.
+    pub fn conditions(&self) -> Vec<String> {
+        if self.is_empty() {
+            return vec![String::from("false")];
+        }
+        let name = |i: usize| &self.names[i - 1];
+        let constraints = self.minimal_constraints();
+        let has = |i: usize, j: usize, c: i128| constraints.contains(&(i, j, c));
+        let mut conditions = Vec::new();
+        for &(i, j, c) in constraints.iter() {
+            // x_i - x_j <= c together with x_j - x_i <= -c is an equality
+            let equal = has(j, i, -c);
+            if equal && j < i {
+                continue;
+            }
+            let op = if equal { "==" } else { "<=" };
+            let flip = !equal && self.orientations.contains(&(j, i))
+                && !self.orientations.contains(&(i, j));
+            conditions.push(match (i, j) {
+                // x_j - x_i >= -c
+                (i, j) if i != 0 && j != 0 && flip => match -c {
+                    1 => format!("{} > {}", name(j), name(i)),
+                    0 => format!("{} >= {}", name(j), name(i)),
+                    k if k > 0 => format!("{} >= {} + {}", name(j), name(i), k),
+                    k => format!("{} >= {} - {}", name(j), name(i), -k),
+                },
+                (0, j) if equal => format!("{} == {}", name(j), -c),
+                (0, j) => format!("{} >= {}", name(j), -c),
+                (i, 0) => format!("{} {} {}", name(i), op, c),
+                (i, j) if c == -1 && !equal => format!("{} < {}", name(i), name(j)),
+                (i, j) if c == 0 => format!("{} {} {}", name(i), op, name(j)),
+                (i, j) if c > 0 => format!("{} {} {} + {}", name(i), op, name(j), c),
+                (i, j) => format!("{} {} {} - {}", name(i), op, name(j), -c),
+            });
+        }
+        conditions
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Dbm;
+
+    fn names() -> Vec<String> {
+        vec![String::from("a"), String::from("b"), String::from("c")]
+    }
+
+    #[test]
+    fn test_closure() {
+        let mut d = Dbm::new(names());
+        d.constrain(Some("a"), None, 1);
+        d.constrain(Some("b"), Some("a"), 2);
+        d.constrain(Some("c"), Some("b"), -1);
+        assert_eq!(Some(3), d.bound(Some("b"), None));
+        assert_eq!(Some(2), d.bound(Some("c"), None));
+        assert_eq!(Some(1), d.bound(Some("c"), Some("a")));
+        assert!(!d.is_empty());
+        d.constrain(None, Some("c"), -3);
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    fn test_inclusion() {
+        let mut d1 = Dbm::new(names());
+        d1.constrain(Some("a"), Some("b"), -1);
+        d1.constrain(Some("a"), None, 1);
+        let mut d2 = d1.clone();
+        d2.constrain(Some("a"), Some("b"), -5);
+        d2.constrain(None, Some("a"), 0);
+        assert!(d1.includes(&d2));
+        assert!(!d2.includes(&d1));
+        assert!(d1.includes(&d1));
+        d2.constrain(Some("a"), None, -1);
+        assert!(d2.is_empty() && d1.includes(&d2));
+    }
+
+    #[test]
+    fn test_join() {
+        let mut d1 = Dbm::new(names());
+        d1.constrain(Some("a"), Some("b"), -1);
+        d1.constrain(Some("a"), None, 1);
+        let mut d2 = Dbm::new(names());
+        d2.constrain(Some("a"), Some("b"), -5);
+        d2.constrain(None, Some("a"), 0);
+        let joined = d1.join(&d2);
+        assert_eq!(Some(-1), joined.bound(Some("a"), Some("b")));
+        assert_eq!(None, joined.bound(Some("a"), None));
+        assert!(joined.includes(&d1) && joined.includes(&d2));
+        let met = d1.meet(&d2);
+        assert_eq!(Some(-5), met.bound(Some("a"), Some("b")));
+        assert_eq!(Some(0), met.bound(None, Some("a")));
+        assert!(d1.includes(&met) && d2.includes(&met));
+        // the join also includes a = 2, b = 10, which satisfies neither
+        assert_eq!(None, d1.exact_join(&d2));
+    }
+
+    #[test]
+    fn test_exact_join() {
+        // a < b && c <= 3 and a >= b && c <= 3
+        let mut d1 = Dbm::new(names());
+        d1.constrain(Some("c"), None, 3);
+        let mut d2 = d1.clone();
+        d1.constrain(Some("a"), Some("b"), -1);
+        d2.constrain(Some("b"), Some("a"), 0);
+        let joined = d1.exact_join(&d2).unwrap();
+        assert_eq!(vec!["c <= 3"], joined.conditions());
+        // a <= 1 and a >= 2 join to no constraint at all
+        let mut d1 = Dbm::new(names());
+        d1.constrain(Some("a"), None, 1);
+        let mut d2 = Dbm::new(names());
+        d2.constrain(None, Some("a"), -2);
+        assert_eq!(0, d1.exact_join(&d2).unwrap().conditions().len());
+        // a <= 1 and a >= 3 leave out a = 2
+        let mut d3 = Dbm::new(names());
+        d3.constrain(None, Some("a"), -3);
+        assert_eq!(None, d1.exact_join(&d3));
+        // one includes the other
+        assert_eq!(Some(d1.clone()), d1.exact_join(&d1.meet(&d3)));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let mut d = Dbm::new(names());
+        d.constrain(Some("a"), None, 1);
+        d.constrain(Some("a"), Some("b"), -1);
+        d.constrain(Some("c"), Some("b"), 0);
+        d.constrain(Some("b"), Some("c"), 0);
+        assert_eq!(vec!["a <= 1", "a < c", "b == c"], d.conditions());
+        // implied by the bounds of a and c
+        d.constrain(None, Some("c"), -8);
+        assert_eq!(vec!["c >= 8", "a <= 1", "b == c"], d.conditions());
+        let mut d = Dbm::new(names());
+        d.constrain(Some("a"), Some("c"), -1);
+        d.constrain(Some("b"), Some("a"), -3);
+        d.orient("c", "a");
+        d.orient("a", "b");
+        assert_eq!(vec!["c > a", "a >= b + 3"], d.conditions());
+    }
+
+    #[test]
+    fn test_complement() {
+        let mut d = Dbm::new(names());
+        d.constrain(Some("a"), Some("b"), 0);
+        let complement = d.complement().unwrap();
+        assert_eq!(Some(-1), complement.bound(Some("b"), Some("a")));
+        assert!(!complement.includes(&d) && !d.includes(&complement));
+        let mut other = Dbm::new(names());
+        other.constrain(Some("b"), Some("a"), 1);
+        assert!(other.includes(&complement));
+        d.constrain(Some("a"), None, 5);
+        assert_eq!(None, d.complement());
+    }
+}
@@ -0,0 +1,328 @@
+use std::cmp::Ordering;
+use std::f64;
+
+/// An end point of an interval of floating point values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bound {
+    pub value: f64,
+    /// whether `value` itself belongs to the interval
+    pub closed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FloatRange {
+    /// disjoint intervals of the value in increasing order, neither of which is
+    /// empty. -0.0 and 0.0 compare equal, so both are represented by 0.0.
+    intervals: Vec<(Bound, Bound)>,
+    /// whether NaN is one of the values
+    nan: bool,
+}
+
+impl Bound {
+    pub fn closed(value: f64) -> Bound {
+        Bound {
+            value: normalize(value),
+            closed: true,
+        }
+    }
+
+    pub fn open(value: f64) -> Bound {
+        Bound {
+            value: normalize(value),
+            closed: false,
+        }
+    }
+
+    /// The bound starting right after this one ends, e.g. (1.0 for 1.0].
+    fn complement(&self) -> Bound {
+        Bound {
+            value: self.value,
+            closed: !self.closed,
+        }
+    }
+}
+
+/// Folds -0.0 into 0.0, as no comparison can tell them apart.
+fn normalize(value: f64) -> f64 {
+    if value == 0.0 {
+        0.0
+    } else {
+        value
+    }
+}
+
+/// Orders lower bounds, [1.0 comes before (1.0.
+fn cmp_lower(a: &Bound, b: &Bound) -> Ordering {
+    match a.value.partial_cmp(&b.value).unwrap() {
+        Ordering::Equal => b.closed.cmp(&a.closed),
+        ordering => ordering,
+    }
+}
+
+/// Orders upper bounds, 1.0) comes before 1.0].
+fn cmp_upper(a: &Bound, b: &Bound) -> Ordering {
+    match a.value.partial_cmp(&b.value).unwrap() {
+        Ordering::Equal => a.closed.cmp(&b.closed),
+        ordering => ordering,
+    }
+}
+
+fn is_empty_interval(lower: &Bound, upper: &Bound) -> bool {
+    lower.value > upper.value || (lower.value == upper.value && !(lower.closed && upper.closed))
+}
+
+impl FloatRange {
+    /// The values between `lower` and `upper`, which must not be NaN.
+    pub fn from(lower: Bound, upper: Bound) -> FloatRange {
+        assert!(!lower.value.is_nan() && !upper.value.is_nan());
+        FloatRange {
+            intervals: if is_empty_interval(&lower, &upper) {
+                vec![]
+            } else {
+                vec![(lower, upper)]
+            },
+            nan: false,
+        }
+    }
+
+    /// Every value, including the infinities and NaN.
+    pub fn full() -> FloatRange {
+        FloatRange {
+            intervals: vec![
+                (Bound::closed(f64::NEG_INFINITY), Bound::closed(f64::INFINITY)),
+            ],
+            nan: true,
+        }
+    }
+
+    pub fn empty() -> FloatRange {
+        FloatRange {
+            intervals: vec![],
+            nan: false,
+        }
+    }
+
+    pub fn nan() -> FloatRange {
+        FloatRange {
+            intervals: vec![],
+            nan: true,
+        }
+    }
+
+    pub fn get_intervals(&self) -> &Vec<(Bound, Bound)> {
+        &self.intervals
+    }
+
+    pub fn has_nan(&self) -> bool {
+        self.nan
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.len() == 0 && !self.nan
+    }
+
+    /// A simple value of the range, i.e. 0.0 or a bound if possible, or None if
+    /// the range is empty.
+    pub fn example(&self) -> Option<f64> {
+        let has_zero = |&&(l, u): &&(Bound, Bound)| {
+            (l.value < 0.0 || l.value == 0.0 && l.closed)
+                && (u.value > 0.0 || u.value == 0.0 && u.closed)
+        };
+        if self.intervals.iter().any(|i| has_zero(&i)) {
+            return Some(0.0);
+        }
+        match self.intervals.first() {
+            Some(&(l, _)) if l.closed && l.value.is_finite() => Some(l.value),
+            Some(&(_, u)) if u.closed && u.value.is_finite() => Some(u.value),
+            Some(&(l, u)) if l.value.is_finite() && u.value.is_finite() => {
+                Some(l.value + (u.value - l.value) / 2.0)
+            }
+            Some(&(l, _)) if l.value.is_finite() => Some(l.value.floor() + 1.0),
+            Some(&(_, u)) if u.value.is_finite() => Some(u.value.ceil() - 1.0),
+            Some(&(l, _)) => Some(l.value),
+            None if self.nan => Some(f64::NAN),
+            None => None,
+        }
+    }
+
+    pub fn complement(&self) -> FloatRange {
+        let mut intervals = Vec::with_capacity(self.intervals.len() + 1);
+        let mut lower = Bound::closed(f64::NEG_INFINITY);
+        for &(l, u) in self.intervals.iter() {
+            let upper = l.complement();
+            if !is_empty_interval(&lower, &upper) {
+                intervals.push((lower, upper));
+            }
+            lower = u.complement();
+        }
+        let upper = Bound::closed(f64::INFINITY);
+        if !is_empty_interval(&lower, &upper) {
+            intervals.push((lower, upper));
+        }
+        FloatRange {
+            intervals,
+            nan: !self.nan,
+        }
+    }
+
+    pub fn intersect(&self, other: &FloatRange) -> FloatRange {
+        let mut intervals = Vec::new();
+        let mut self_index = 0;
+        let mut other_index = 0;
+        while self_index < self.intervals.len() && other_index < other.intervals.len() {
+            let (a, b) = self.intervals[self_index];
+            let (c, d) = other.intervals[other_index];
+            let lower = if cmp_lower(&a, &c) == Ordering::Greater {
+                a
+            } else {
+                c
+            };
+            let upper = if cmp_upper(&b, &d) == Ordering::Less {
+                b
+            } else {
+                d
+            };
+            if !is_empty_interval(&lower, &upper) {
+                intervals.push((lower, upper));
+            }
+            if cmp_upper(&b, &d) == Ordering::Less {
+                self_index += 1;
+            } else {
+                other_index += 1;
+            }
+        }
+        FloatRange {
+            intervals,
+            nan: self.nan && other.nan,
+        }
+    }
+
+    pub fn union(&self, other: &FloatRange) -> FloatRange {
+        let mut all: Vec<(Bound, Bound)> = self.intervals
+            .iter()
+            .chain(other.intervals.iter())
+            .cloned()
+            .collect();
+        all.sort_by(|&(a, _), &(c, _)| cmp_lower(&a, &c));
+        let mut intervals: Vec<(Bound, Bound)> = Vec::with_capacity(all.len());
+        for (l, u) in all.into_iter() {
+            if let Some(last) = intervals.last_mut() {
+                // overlapping or touching, e.g. [0.0, 1.0) and [1.0, 2.0]
+                let touching = last.1.value == l.value && (last.1.closed || l.closed);
+                if last.1.value > l.value || touching {
+                    if cmp_upper(&u, &last.1) == Ordering::Greater {
+                        last.1 = u;
+                    }
+                    continue;
+                }
+            }
+            intervals.push((l, u));
+        }
+        FloatRange {
+            intervals,
+            nan: self.nan || other.nan,
+        }
+    }
+
+    pub fn difference(&self, other: &FloatRange) -> FloatRange {
+        self.intersect(&other.complement())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Bound, FloatRange};
+    use std::f64;
+
+    #[test]
+    fn test_complement() {
+        let r = FloatRange::from(Bound::open(0.0), Bound::closed(1.0));
+        let c = r.complement();
+        assert_eq!(
+            &vec![
+                (Bound::closed(f64::NEG_INFINITY), Bound::closed(0.0)),
+                (Bound::open(1.0), Bound::closed(f64::INFINITY)),
+            ],
+            c.get_intervals()
+        );
+        assert!(c.has_nan());
+        assert_eq!(r, c.complement());
+        assert!(FloatRange::full().complement().is_empty());
+    }
+
+    #[test]
+    fn test_example() {
+        let r = FloatRange::from(Bound::open(0.0), Bound::closed(1.0));
+        assert_eq!(Some(1.0), r.example());
+        assert_eq!(Some(0.0), r.complement().example());
+        let r = FloatRange::from(Bound::open(0.5), Bound::closed(f64::INFINITY));
+        assert_eq!(Some(1.0), r.example());
+        assert!(FloatRange::nan().example().unwrap().is_nan());
+        assert_eq!(None, FloatRange::empty().example());
+    }
+
+    #[test]
+    fn test_negative_zero() {
+        let r = FloatRange::from(Bound::closed(-0.0), Bound::closed(-0.0));
+        assert_eq!(FloatRange::from(Bound::closed(0.0), Bound::closed(0.0)), r);
+        let test = FloatRange::from(Bound::closed(-1.0), Bound::open(0.0)).union(&r);
+        assert_eq!(
+            &vec![(Bound::closed(-1.0), Bound::closed(0.0))],
+            test.get_intervals()
+        );
+    }
+
+    #[test]
+    fn test_open_bounds_intersection() {
+        let r1 = FloatRange::from(Bound::closed(0.0), Bound::open(1.0));
+        let r2 = FloatRange::from(Bound::closed(1.0), Bound::closed(2.0));
+        assert!(r1.intersect(&r2).is_empty());
+        let r3 = FloatRange::from(Bound::open(0.0), Bound::closed(1.0));
+        assert_eq!(
+            &vec![(Bound::open(0.0), Bound::open(1.0))],
+            r1.intersect(&r3).get_intervals()
+        );
+    }
+
+    #[test]
+    fn test_union() {
+        let r1 = FloatRange::from(Bound::closed(0.0), Bound::open(1.0));
+        let r2 = FloatRange::from(Bound::open(1.0), Bound::closed(2.0));
+        assert_eq!(
+            &vec![
+                (Bound::closed(0.0), Bound::open(1.0)),
+                (Bound::open(1.0), Bound::closed(2.0)),
+            ],
+            r1.union(&r2).get_intervals()
+        );
+        let r3 = FloatRange::from(Bound::closed(1.0), Bound::closed(1.0));
+        assert_eq!(
+            &vec![(Bound::closed(0.0), Bound::closed(2.0))],
+            r1.union(&r2).union(&r3).get_intervals()
+        );
+    }
+
+    #[test]
+    fn test_difference() {
+        let full = FloatRange::full();
+        let test = full.difference(&FloatRange::nan());
+        assert!(!test.has_nan());
+        assert_eq!(
+            &vec![
+                (
+                    Bound::closed(f64::NEG_INFINITY),
+                    Bound::closed(f64::INFINITY)
+                ),
+            ],
+            test.get_intervals()
+        );
+        let test = test.difference(&FloatRange::from(
+            Bound::closed(f64::INFINITY),
+            Bound::closed(f64::INFINITY),
+        ));
+        assert_eq!(
+            &vec![(Bound::closed(f64::NEG_INFINITY), Bound::open(f64::INFINITY))],
+            test.get_intervals()
+        );
+    }
+}
@@ -0,0 +1,477 @@
+use regex::Regex;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+
+use super::super::{construct_path, parse_java_method, read_file_to_string, Config};
+use super::strings;
+
+static PRIMITIVES: [&str; 8] = [
+    "boolean", "byte", "char", "short", "int", "long", "float", "double",
+];
+
+#[derive(Debug)]
+struct DriverError {
+    description: String,
+}
+
+impl DriverError {
+    fn no_source(class: &str) -> DriverError {
+        DriverError {
+            description: format!("Unable to find the source of {} in the classpath", class),
+        }
+    }
+
+    fn compilation_failed(source_path: &str) -> DriverError {
+        DriverError {
+            description: format!("Unable to compile the driver {}", source_path),
+        }
+    }
+}
+
+impl fmt::Display for DriverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", &self.description)
+    }
+}
+
+impl Error for DriverError {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// A parameter of a Java method.
+#[derive(Debug, PartialEq)]
+struct Parameter {
+    typ: String,
+    name: String,
+    /// the primitive fields of an object parameter as (type, name)
+    fields: Vec<(String, String)>,
+    /// whether the parameter is a string built from an array of characters
+    string: bool,
+}
+
+/// A generated class calling a method with object parameters from a method
+/// taking the primitive fields of these objects instead, which JDart can make
+/// symbolic. If enabled, it also takes strings as arrays of characters along
+/// with whether they are null.
+#[derive(Debug)]
+pub struct Driver {
+    /// the name of the generated class
+    pub class: String,
+    /// the declaration of the method of the generated class
+    pub method: String,
+    /// the signature of the original method as JDart names methods
+    pub signature: String,
+    /// the directory the generated class is compiled to
+    pub classpath: String,
+    source_path: String,
+    /// the parameters of the driver standing for fields, e.g. `other_state`
+    /// for `other.state`
+    renames: Vec<(String, String)>,
+    /// the string parameters the driver takes as arrays of characters
+    strings: Vec<String>,
+}
+
+/// The instance fields of a primitive type the Java source of a class declares
+/// and code in the same package can assign, as (type, name). Only declarations
+/// directly in the body of the outermost class count.
+fn primitive_fields(source: &str) -> Vec<(String, String)> {
+    lazy_static! {
+        static ref FIELD: Regex = Regex::new(concat!(
+            r"(?m)^\s*((?:(?:public|protected|private|static|final|transient|volatile)\s+)*)",
+            r"(boolean|byte|char|short|int|long|float|double)\s+(\w+)\s*(?:=[^;]*)?;"
+        )).unwrap();
+    }
+    let depth = |end: usize| {
+        source[..end].chars().fold(0, |depth, c| match c {
+            '{' => depth + 1,
+            '}' => depth - 1,
+            _ => depth,
+        })
+    };
+    FIELD
+        .captures_iter(source)
+        .filter(|cap| {
+            let modifiers = &cap[1];
+            !modifiers.contains("private") && !modifiers.contains("static")
+                && !modifiers.contains("final")
+                && depth(cap.get(0).unwrap().start()) == 1
+        })
+        .map(|cap| (String::from(&cap[2]), String::from(&cap[3])))
+        .collect()
+}
+
+/// The Java source of `class` in `package`, next to the classes in the
+/// classpath.
+fn class_source(config: &Config, package: &str, class: &str) -> Result<String, Box<Error>> {
+    let relative_path = format!("{}/{}.java", package.replace('.', "/"), class);
+    config
+        .classpath
+        .iter()
+        .filter_map(|classpath| {
+            let path = construct_path(&PathBuf::from(classpath), &relative_path).ok()?;
+            read_file_to_string(&path).ok()
+        })
+        .next()
+        .ok_or_else(|| From::from(DriverError::no_source(class)))
+}
+
+/// The return type, name and parameters of a method declaration, e.g.
+/// `int isPositive(IsPositive other, int i)`.
+fn parse_declaration(decl: &str) -> Option<(String, String, Vec<(String, String)>)> {
+    lazy_static! {
+        static ref DECL: Regex =
+            Regex::new(r"(?P<ret>[\w\[\]<>.]+)\s+(?P<name>\w+)\s*\((?P<params>[^)]*)\)").unwrap();
+    }
+    let cap = DECL.captures(decl)?;
+    let mut params = Vec::new();
+    for param in cap["params"].split(',').filter(|p| p.trim().len() != 0) {
+        let words: Vec<&str> = param
+            .split_whitespace()
+            .filter(|w| !w.starts_with('@') && *w != "final")
+            .collect();
+        if words.len() != 2 {
+            return None;
+        }
+        params.push((String::from(words[0]), String::from(words[1])));
+    }
+    Some((String::from(&cap["ret"]), String::from(&cap["name"]), params))
+}
+
+/// The source of the driver method, which constructs the receiver, given by
+/// its fields unless the method is static, and the object parameters from
+/// their fields and the strings from their characters, and calls the method.
+fn driver_source(
+    package: &str,
+    class: &str,
+    driver_class: &str,
+    ret: &str,
+    name: &str,
+    receiver: Option<&[(String, String)]>,
+    params: &[Parameter],
+) -> String {
+    let mut driver_params = Vec::new();
+    let mut body = Vec::new();
+    let receiver = match receiver {
+        None => String::from(class),
+        Some(fields) if fields.len() == 0 => format!("new {}()", class),
+        Some(fields) => {
+            body.push(format!("    {} $this = new {}();", class, class));
+            for &(ref typ, ref field) in fields.iter() {
+                driver_params.push(format!("{} this_{}", typ, field));
+                body.push(format!("    $this.{} = this_{};", field, field));
+            }
+            String::from("$this")
+        }
+    };
+    for param in params.iter() {
+        if param.string {
+            let (null, chars) = (
+                strings::null_parameter(&param.name),
+                strings::chars_parameter(&param.name),
+            );
+            driver_params.push(format!("boolean {}", null));
+            driver_params.push(format!("char[] {}", chars));
+            body.push(format!(
+                "    {} {} = {} ? null : new String({});",
+                param.typ, param.name, null, chars
+            ));
+            continue;
+        }
+        if param.fields.len() == 0 {
+            driver_params.push(format!("{} {}", param.typ, param.name));
+            continue;
+        }
+        body.push(format!("    {} {} = new {}();", param.typ, param.name, param.typ));
+        for &(ref typ, ref field) in param.fields.iter() {
+            driver_params.push(format!("{} {}_{}", typ, param.name, field));
+            body.push(format!("    {}.{} = {}_{};", param.name, field, param.name, field));
+        }
+    }
+    let args: Vec<&str> = params.iter().map(|p| p.name.as_str()).collect();
+    let call = format!("{}.{}({})", receiver, name, args.join(", "));
+    body.push(if ret == "void" {
+        format!("    {};", call)
+    } else {
+        format!("    return {};", call)
+    });
+    format!(
+        "package {};\n\npublic class {} {{\n  public static {} {}({}) {{\n{}\n  }}\n}}\n",
+        package,
+        driver_class,
+        ret,
+        name,
+        driver_params.join(", "),
+        body.join("\n")
+    )
+}
+
+impl Driver {
+    /// Generates the driver of `method` if it has object parameters, from the
+    /// sources of their classes, which must be in the same package as `class`
+    /// and next to the classes in the classpath, or string parameters and
+    /// their length is bounded. The driver also takes the fields of the
+    /// receiver, e.g. `this_state` for `this.state`. It constructs the receiver
+    /// and the object parameters with the constructors without parameters,
+    /// which must exist.
+    pub fn generate(
+        config: &Config,
+        output_path: &PathBuf,
+        package: &str,
+        class: &str,
+        method: &str,
+    ) -> Result<Option<Driver>, Box<Error>> {
+        let (ret, name, params) = match parse_declaration(method) {
+            Some(decl) => decl,
+            None => return Ok(None),
+        };
+        let encode_strings = config.jdart_options(&name).max_string_length.is_some();
+        let mut parameters = Vec::new();
+        let mut renames = Vec::new();
+        for (typ, param_name) in params.into_iter() {
+            let is_string = typ == "String" || typ == "java.lang.String";
+            let is_object = !PRIMITIVES.contains(&typ.as_str()) && !typ.ends_with("[]")
+                && !is_string;
+            let fields = if is_object {
+                primitive_fields(&class_source(config, package, &typ)?)
+            } else {
+                Vec::new()
+            };
+            for &(_, ref field) in fields.iter() {
+                renames.push((
+                    format!("{}_{}", param_name, field),
+                    format!("{}.{}", param_name, field),
+                ));
+            }
+            parameters.push(Parameter {
+                typ,
+                name: param_name,
+                fields,
+                string: is_string && encode_strings,
+            });
+        }
+        if renames.len() == 0 && !parameters.iter().any(|param| param.string) {
+            return Ok(None);
+        }
+        let receiver = if method.contains("static ") {
+            None
+        } else {
+            let fields = primitive_fields(&class_source(config, package, class)?);
+            for &(_, ref field) in fields.iter() {
+                renames.push((format!("this_{}", field), format!("this.{}", field)));
+            }
+            Some(fields)
+        };
+        let strings = parameters
+            .iter()
+            .filter(|param| param.string)
+            .map(|param| param.name.clone())
+            .collect();
+        let driver_class = format!("{}Driver", class);
+        let source = driver_source(
+            package,
+            class,
+            &driver_class,
+            &ret,
+            &name,
+            receiver.as_ref().map(|fields| fields.as_slice()),
+            &parameters,
+        );
+        let source_dir = output_path.join("driver").join(package.replace('.', "/"));
+        fs::create_dir_all(&source_dir)?;
+        let source_path = construct_path(&source_dir, &format!("{}.java", driver_class))?;
+        File::create(&source_path)?.write_all(source.as_bytes())?;
+        // the declaration of the driver method, e.g. `int isPositive(int other_state, int i)`
+        let start = source.find("public static ").unwrap() + "public static ".len();
+        let end = source[start..].find(" {").unwrap() + start;
+        Ok(Some(Driver {
+            class: driver_class,
+            method: String::from(&source[start..end]),
+            signature: parse_java_method(package, class, method)?.1,
+            classpath: construct_path(output_path, "driver")?,
+            source_path,
+            renames,
+            strings,
+        }))
+    }
+
+    /// Compiles the driver next to its source, against the classpath.
+    pub fn compile(&self, config: &Config) -> Result<(), Box<Error>> {
+        let status = Command::new("javac")
+            .arg("-cp")
+            .arg(config.classpath.join(":"))
+            .arg("-d")
+            .arg(&self.classpath)
+            .arg(&self.source_path)
+            .status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(Box::new(DriverError::compilation_failed(&self.source_path)))
+        }
+    }
+
+    /// Refers to the fields and strings the driver takes as parameters by
+    /// their names in the original method, e.g. `other.state` for
+    /// `other_state` and `s.length()` for `s$chars.length`.
+    pub fn rename(&self, s: &str) -> String {
+        let mut renamed = strings::rename(s);
+        for &(ref from, ref to) in self.renames.iter() {
+            let re = Regex::new(&format!(r"\b{}\b", ::regex::escape(from))).unwrap();
+            renamed = String::from(re.replace_all(&renamed, to.as_str()));
+        }
+        renamed
+    }
+
+    /// Refers to the fields and strings of the original method by the
+    /// parameters of the driver, e.g. `other_state` for `other.state` and
+    /// `s$chars.length` for `s.length()`, the inverse of `rename`.
+    pub fn encode(&self, s: &str) -> String {
+        let mut encoded = String::from(s);
+        for string in self.strings.iter() {
+            encoded = strings::encode(&encoded, string);
+        }
+        for &(ref to, ref from) in self.renames.iter() {
+            let re = Regex::new(&format!(r"\b{}\b", ::regex::escape(from))).unwrap();
+            encoded = String::from(re.replace_all(&encoded, to.as_str()));
+        }
+        encoded
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{driver_source, parse_declaration, primitive_fields, Driver, Parameter};
+
+    #[test]
+    fn test_primitive_fields() {
+        let source = "
+public class IsPositive {
+  protected int state;
+  private long secret;
+  static int count = 0;
+  public double ratio = 0.5;
+  String name;
+  public int isPositive(IsPositive other, int i) {
+    int local = 0;
+    return local;
+  }
+}
+";
+        assert_eq!(
+            vec![
+                (String::from("int"), String::from("state")),
+                (String::from("double"), String::from("ratio")),
+            ],
+            primitive_fields(source)
+        );
+    }
+
+    #[test]
+    fn test_driver_source() {
+        let (ret, name, params) =
+            parse_declaration("public int isPositive(IsPositive other, int i)").unwrap();
+        assert_eq!(("int", "isPositive"), (ret.as_str(), name.as_str()));
+        let params: Vec<Parameter> = params
+            .into_iter()
+            .map(|(typ, name)| Parameter {
+                fields: if typ == "IsPositive" {
+                    vec![(String::from("int"), String::from("state"))]
+                } else {
+                    Vec::new()
+                },
+                string: false,
+                typ,
+                name,
+            })
+            .collect();
+        assert_eq!(
+            "package examples;
+
+public class IsPositiveDriver {
+  public static int isPositive(int this_state, int other_state, int i) {
+    IsPositive $this = new IsPositive();
+    $this.state = this_state;
+    IsPositive other = new IsPositive();
+    other.state = other_state;
+    return $this.isPositive(other, i);
+  }
+}
+",
+            driver_source(
+                "examples",
+                "IsPositive",
+                "IsPositiveDriver",
+                &ret,
+                &name,
+                Some(&[(String::from("int"), String::from("state"))]),
+                &params
+            )
+        );
+    }
+
+    #[test]
+    fn test_string_driver_source() {
+        let params = vec![
+            Parameter {
+                typ: String::from("String"),
+                name: String::from("string"),
+                fields: Vec::new(),
+                string: true,
+            },
+            Parameter {
+                typ: String::from("int"),
+                name: String::from("count"),
+                fields: Vec::new(),
+                string: false,
+            },
+        ];
+        assert_eq!(
+            "package examples;
+
+public class GuavaStringsDriver {
+  public static String repeat(boolean string$null, char[] string$chars, int count) {
+    String string = string$null ? null : new String(string$chars);
+    return GuavaStrings.repeat(string, count);
+  }
+}
+",
+            driver_source(
+                "examples",
+                "GuavaStrings",
+                "GuavaStringsDriver",
+                "String",
+                "repeat",
+                None,
+                &params
+            )
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        let driver = Driver {
+            class: String::from("IsPositiveDriver"),
+            method: String::from("int isPositive(int other_state, boolean s$null, char[] s$chars)"),
+            signature: String::from("examples.IsPositive.isPositive(other:IsPositive,s:String,)"),
+            classpath: String::from("driver"),
+            source_path: String::from("driver/examples/IsPositiveDriver.java"),
+            renames: vec![
+                (String::from("this_state"), String::from("this.state")),
+                (String::from("other_state"), String::from("other.state")),
+            ],
+            strings: vec![String::from("s")],
+        };
+        let assumption = "this.state < other.state && s != null && s.length() <= 4";
+        assert_eq!(
+            "this_state < other_state && !s$null && s$chars.length <= 4",
+            driver.encode(assumption)
+        );
+        assert_eq!("other.state > 0", driver.rename(&driver.encode("other.state > 0")));
+    }
+}
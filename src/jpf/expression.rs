@@ -1,40 +1,690 @@
 use nom::{digit, IResult};
 use regex::Regex;
-use std::collections::HashMap;
-use std::{str, i16, i32, i64, i8};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::{str, f64, i128, i16, i32, i64, i8, u16, u32, u64, u8};
 
+use super::super::float_range::{Bound, FloatRange};
 use super::super::range::Range;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Type {
+    /// Java's boolean, with false as 0 and true as 1
+    Bool,
     SInt8,
     SInt16,
     SInt32,
     SInt64,
+    UInt8,
+    /// Java's char
+    UInt16,
+    UInt32,
+    UInt64,
+    /// Java's float
+    Float32,
+    /// Java's double
+    Float64,
 }
 
 impl Type {
+    /// The values of an integral type.
     pub fn range(&self) -> Range {
         match self {
-            &Type::SInt8 => Range::from(i8::MIN as i64, i8::MAX as i64),
-            &Type::SInt16 => Range::from(i16::MIN as i64, i16::MAX as i64),
-            &Type::SInt32 => Range::from(i32::MIN as i64, i32::MAX as i64),
-            &Type::SInt64 => Range::from(i64::MIN, i64::MAX),
+            &Type::Bool => Range::from(0, 1),
+            &Type::SInt8 => Range::from(i8::MIN as i128, i8::MAX as i128),
+            &Type::SInt16 => Range::from(i16::MIN as i128, i16::MAX as i128),
+            &Type::SInt32 => Range::from(i32::MIN as i128, i32::MAX as i128),
+            &Type::SInt64 => Range::from(i64::MIN as i128, i64::MAX as i128),
+            &Type::UInt8 => Range::from(0, u8::MAX as i128),
+            &Type::UInt16 => Range::from(0, u16::MAX as i128),
+            &Type::UInt32 => Range::from(0, u32::MAX as i128),
+            &Type::UInt64 => Range::from(0, u64::MAX as i128),
+            &Type::Float32 | &Type::Float64 => unreachable!(),
+        }
+    }
+
+    /// All values of the type.
+    pub fn domain(&self) -> Domain {
+        if self.is_float() {
+            Domain::Float(FloatRange::full())
+        } else {
+            Domain::Integer(self.range())
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        *self == Type::Float32 || *self == Type::Float64
+    }
+
+    /// Number of bits of the type.
+    fn width(&self) -> u32 {
+        match *self {
+            Type::Bool => 1,
+            Type::SInt8 | Type::UInt8 => 8,
+            Type::SInt16 | Type::UInt16 => 16,
+            Type::SInt32 | Type::UInt32 | Type::Float32 => 32,
+            Type::SInt64 | Type::UInt64 | Type::Float64 => 64,
+        }
+    }
+
+    /// Whether every value of `other` is also a value of this type.
+    fn contains(&self, other: &Type) -> bool {
+        match (self.is_float(), other.is_float()) {
+            (true, true) => self.width() >= other.width(),
+            // integers fitting into the mantissa are converted exactly
+            (true, false) => other.width() <= if *self == Type::Float32 { 16 } else { 32 },
+            (false, true) => false,
+            (false, false) => {
+                let (lower, upper) = self.range().get_ranges()[0];
+                let (other_lower, other_upper) = other.range().get_ranges()[0];
+                lower <= other_lower && other_upper <= upper
+            }
+        }
+    }
+
+    /// Converts `val` to the type the way a Java cast does, i.e. keeping the
+    /// lowest bits and sign extending them for signed types.
+    fn wrap(&self, val: i128) -> i128 {
+        match *self {
+            Type::Bool => val & 1,
+            Type::SInt8 => val as i8 as i128,
+            Type::SInt16 => val as i16 as i128,
+            Type::SInt32 => val as i32 as i128,
+            Type::SInt64 => val as i64 as i128,
+            Type::UInt8 => val as u8 as i128,
+            Type::UInt16 => val as u16 as i128,
+            Type::UInt32 => val as u32 as i128,
+            Type::UInt64 => val as u64 as i128,
+            Type::Float32 | Type::Float64 => unreachable!(),
+        }
+    }
+
+    /// Converts `val` to the type the way a Java cast does. Casts to integral
+    /// types round towards zero, saturate at the bounds of int or long and map
+    /// NaN to zero, casts to float round to the nearest float.
+    fn convert(&self, val: f64) -> Term {
+        match *self {
+            Type::Float32 => Term::Float(val as f32 as f64),
+            Type::Float64 => Term::Float(val),
+            Type::SInt64 => Term::Constant(val as i64 as i128),
+            _ => Term::Constant(self.wrap(val as i32 as i128)),
+        }
+    }
+
+    /// Formats a value of the type as a Java literal.
+    pub fn literal(&self, val: i128) -> String {
+        match *self {
+            Type::Bool => String::from(if val == 0 { "false" } else { "true" }),
+            Type::UInt16 => match val as u8 as char {
+                '\'' | '\\' if val < 0x80 => format!("'\\{}'", val as u8 as char),
+                c if val >= 0x20 && val < 0x7f => format!("'{}'", c),
+                _ => format!("'\\u{:04x}'", val),
+            },
+            _ => format!("{}", val),
+        }
+    }
+
+    /// Formats a floating point value of the type as a Java literal.
+    pub fn float_literal(&self, val: f64) -> String {
+        let class = if *self == Type::Float32 {
+            "Float"
+        } else {
+            "Double"
+        };
+        if val.is_nan() {
+            format!("{}.NaN", class)
+        } else if val == f64::INFINITY {
+            format!("{}.POSITIVE_INFINITY", class)
+        } else if val == f64::NEG_INFINITY {
+            format!("{}.NEGATIVE_INFINITY", class)
+        } else if *self == Type::Float32 {
+            format!("{:?}f", val as f32)
+        } else {
+            format!("{:?}", val)
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Type::Bool => "boolean",
+                Type::SInt8 => "byte",
+                Type::SInt16 => "short",
+                Type::SInt32 => "int",
+                Type::SInt64 => "long",
+                Type::UInt8 => "uint8",
+                Type::UInt16 => "char",
+                Type::UInt32 => "uint32",
+                Type::UInt64 => "uint64",
+                Type::Float32 => "float",
+                Type::Float64 => "double",
+            }
+        )
+    }
+}
+
+/// The values of a variable, which are intervals of integers for the integral
+/// types and intervals of IEEE values for float and double.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Domain {
+    Integer(Range),
+    Float(FloatRange),
+}
+
+impl Domain {
+    pub fn as_integer(&self) -> Option<&Range> {
+        match *self {
+            Domain::Integer(ref range) => Some(range),
+            Domain::Float(_) => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match *self {
+            Domain::Integer(ref range) => range.get_ranges().len() == 0,
+            Domain::Float(ref range) => range.is_empty(),
+        }
+    }
+
+    /// Whether the domain may contain values it should not, see
+    /// `Range::is_approximate`.
+    pub fn is_approximate(&self) -> bool {
+        match *self {
+            Domain::Integer(ref range) => range.is_approximate(),
+            Domain::Float(_) => false,
+        }
+    }
+
+    pub fn union(&self, other: &Domain) -> Domain {
+        match (self, other) {
+            (&Domain::Integer(ref a), &Domain::Integer(ref b)) => Domain::Integer(a.union(b)),
+            (&Domain::Float(ref a), &Domain::Float(ref b)) => Domain::Float(a.union(b)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The values in either domain, or None if they cannot be represented
+    /// exactly.
+    pub fn exact_union(&self, other: &Domain) -> Option<Domain> {
+        match (self, other) {
+            (&Domain::Integer(ref a), &Domain::Integer(ref b)) => {
+                a.exact_union(b).map(Domain::Integer)
+            }
+            (&Domain::Float(ref a), &Domain::Float(ref b)) => Some(Domain::Float(a.union(b))),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn intersect(&self, other: &Domain) -> Domain {
+        match (self, other) {
+            (&Domain::Integer(ref a), &Domain::Integer(ref b)) => Domain::Integer(a.intersect(b)),
+            (&Domain::Float(ref a), &Domain::Float(ref b)) => Domain::Float(a.intersect(b)),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The values of this domain that are not in `other`, which may
+    /// over-approximate for integers (see `Range::difference`).
+    pub fn difference(&self, other: &Domain) -> Domain {
+        match (self, other) {
+            (&Domain::Integer(ref a), &Domain::Integer(ref b)) => Domain::Integer(a.difference(b)),
+            (&Domain::Float(ref a), &Domain::Float(ref b)) => Domain::Float(a.difference(b)),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Variable {
     pub name: String,
     pub typ: Type,
-    pub range: Range,
+    pub range: Domain,
+    /// whether `range` over-approximates the values allowed by the condition
+    pub approximate: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Term {
+    Variable(String),
+    Constant(i128),
+    Float(f64),
+    Negate(Box<Term>),
+    Binary(Box<Term>, Operator, Box<Term>),
+    Cast(Type, Box<Term>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl,
+    Shr,
+    UShr,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparator {
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Eq,
+    Neq,
+}
+
+/// A comparison between two terms that cannot be folded into the range of a
+/// single variable, e.g. `i <= other.state`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Relation {
+    pub lhs: Term,
+    pub comparator: Comparator,
+    pub rhs: Term,
+    /// whether the comparison must not hold, which is not the same as the
+    /// negated comparator holding when one side may be NaN
+    pub negated: bool,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Condition {
     True,
-    Conditions(HashMap<String, Variable>),
+    Conditions(HashMap<String, Variable>, Vec<Relation>),
+    /// Holds when any of the contained `Conditions` holds.
+    Disjunction(Vec<Condition>),
+}
+
+impl Comparator {
+    /// The comparator to use when the operands are swapped, e.g. `2 < a`
+    /// becomes `a > 2`.
+    fn flip(&self) -> Comparator {
+        match *self {
+            Comparator::Lt => Comparator::Gt,
+            Comparator::Lte => Comparator::Gte,
+            Comparator::Gt => Comparator::Lt,
+            Comparator::Gte => Comparator::Lte,
+            Comparator::Eq => Comparator::Eq,
+            Comparator::Neq => Comparator::Neq,
+        }
+    }
+
+    /// The comparator that holds exactly when this one does not.
+    fn negate(&self) -> Comparator {
+        match *self {
+            Comparator::Lt => Comparator::Gte,
+            Comparator::Lte => Comparator::Gt,
+            Comparator::Gt => Comparator::Lte,
+            Comparator::Gte => Comparator::Lt,
+            Comparator::Eq => Comparator::Neq,
+            Comparator::Neq => Comparator::Eq,
+        }
+    }
+
+    fn holds(&self, lhs: i128, rhs: i128) -> bool {
+        match *self {
+            Comparator::Lt => lhs < rhs,
+            Comparator::Lte => lhs <= rhs,
+            Comparator::Gt => lhs > rhs,
+            Comparator::Gte => lhs >= rhs,
+            Comparator::Eq => lhs == rhs,
+            Comparator::Neq => lhs != rhs,
+        }
+    }
+
+    fn holds_float(&self, lhs: f64, rhs: f64) -> bool {
+        match *self {
+            Comparator::Lt => lhs < rhs,
+            Comparator::Lte => lhs <= rhs,
+            Comparator::Gt => lhs > rhs,
+            Comparator::Gte => lhs >= rhs,
+            Comparator::Eq => lhs == rhs,
+            Comparator::Neq => lhs != rhs,
+        }
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Comparator::Lt => "<",
+                Comparator::Lte => "<=",
+                Comparator::Gt => ">",
+                Comparator::Gte => ">=",
+                Comparator::Eq => "==",
+                Comparator::Neq => "!=",
+            }
+        )
+    }
+}
+
+impl Operator {
+    /// Java operator precedence, higher binds tighter.
+    fn precedence(&self) -> u8 {
+        match *self {
+            Operator::BitOr => 1,
+            Operator::BitXor => 2,
+            Operator::BitAnd => 3,
+            Operator::Shl | Operator::Shr | Operator::UShr => 4,
+            Operator::Add | Operator::Sub => 5,
+            Operator::Mul | Operator::Div | Operator::Rem => 6,
+        }
+    }
+
+    /// Evaluates the operator on two constants with the semantics of Java's
+    /// int arithmetic.
+    fn eval_int(&self, lhs: i32, rhs: i32) -> Option<i32> {
+        match *self {
+            Operator::Add => Some(lhs.wrapping_add(rhs)),
+            Operator::Sub => Some(lhs.wrapping_sub(rhs)),
+            Operator::Mul => Some(lhs.wrapping_mul(rhs)),
+            Operator::Div if rhs == 0 => None,
+            Operator::Div => Some(lhs.wrapping_div(rhs)),
+            Operator::Rem if rhs == 0 => None,
+            Operator::Rem => Some(lhs.wrapping_rem(rhs)),
+            Operator::BitAnd => Some(lhs & rhs),
+            Operator::BitOr => Some(lhs | rhs),
+            Operator::BitXor => Some(lhs ^ rhs),
+            // Java only uses the lowest five bits of the shift distance
+            Operator::Shl => Some(lhs.wrapping_shl((rhs & 0x1f) as u32)),
+            Operator::Shr => Some(lhs.wrapping_shr((rhs & 0x1f) as u32)),
+            Operator::UShr => Some(((lhs as u32) >> (rhs & 0x1f)) as i32),
+        }
+    }
+
+    /// Evaluates the operator on two constants with the semantics of Java's
+    /// long arithmetic.
+    fn eval_long(&self, lhs: i64, rhs: i64) -> Option<i64> {
+        match *self {
+            Operator::Add => Some(lhs.wrapping_add(rhs)),
+            Operator::Sub => Some(lhs.wrapping_sub(rhs)),
+            Operator::Mul => Some(lhs.wrapping_mul(rhs)),
+            Operator::Div if rhs == 0 => None,
+            Operator::Div => Some(lhs.wrapping_div(rhs)),
+            Operator::Rem if rhs == 0 => None,
+            Operator::Rem => Some(lhs.wrapping_rem(rhs)),
+            Operator::BitAnd => Some(lhs & rhs),
+            Operator::BitOr => Some(lhs | rhs),
+            Operator::BitXor => Some(lhs ^ rhs),
+            // Java only uses the lowest six bits of the shift distance
+            Operator::Shl => Some(lhs.wrapping_shl((rhs & 0x3f) as u32)),
+            Operator::Shr => Some(lhs.wrapping_shr((rhs & 0x3f) as u32)),
+            Operator::UShr => Some(((lhs as u64) >> (rhs & 0x3f)) as i64),
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                Operator::Add => "+",
+                Operator::Sub => "-",
+                Operator::Mul => "*",
+                Operator::Div => "/",
+                Operator::Rem => "%",
+                Operator::BitAnd => "&",
+                Operator::BitOr => "|",
+                Operator::BitXor => "^",
+                Operator::Shl => "<<",
+                Operator::Shr => ">>",
+                Operator::UShr => ">>>",
+            }
+        )
+    }
+}
+
+impl Term {
+    fn precedence(&self) -> u8 {
+        match *self {
+            Term::Binary(_, op, _) => op.precedence(),
+            // casts to long never change the value and are not shown
+            Term::Cast(Type::SInt64, ref t) => t.precedence(),
+            Term::Negate(_) | Term::Cast(..) => 7,
+            Term::Constant(val) if val < 0 => 7,
+            Term::Float(val) if val < 0.0 && val.is_finite() => 7,
+            _ => 8,
+        }
+    }
+
+    fn variables<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match *self {
+            Term::Variable(ref name) => names.push(name),
+            Term::Constant(_) | Term::Float(_) => {}
+            Term::Negate(ref t) => t.variables(names),
+            Term::Binary(ref lhs, _, ref rhs) => {
+                lhs.variables(names);
+                rhs.variables(names);
+            }
+            Term::Cast(_, ref t) => t.variables(names),
+        }
+    }
+
+    fn has_cast(&self) -> bool {
+        match *self {
+            Term::Variable(_) | Term::Constant(_) | Term::Float(_) => false,
+            Term::Negate(ref t) => t.has_cast(),
+            Term::Binary(ref lhs, _, ref rhs) => lhs.has_cast() || rhs.has_cast(),
+            Term::Cast(..) => true,
+        }
+    }
+
+    /// The type of the term after Java's binary numeric promotion, or None
+    /// for integer constants, which adapt to the other operand.
+    fn typ(&self, vars: &HashMap<String, Variable>) -> Option<Type> {
+        let promote = |types: &[Option<Type>]| if types.contains(&Some(Type::Float64)) {
+            Type::Float64
+        } else if types.contains(&Some(Type::Float32)) {
+            Type::Float32
+        } else if types
+            .iter()
+            .any(|typ| typ.as_ref().map_or(false, |typ| typ.width() > 32))
+        {
+            Type::SInt64
+        } else {
+            Type::SInt32
+        };
+        match *self {
+            Term::Variable(ref name) => Some(vars[name].typ.clone()),
+            Term::Constant(_) => None,
+            Term::Float(_) => Some(Type::Float64),
+            Term::Negate(ref t) => Some(promote(&[t.typ(vars)])),
+            Term::Binary(ref lhs, _, ref rhs) => Some(promote(&[lhs.typ(vars), rhs.typ(vars)])),
+            Term::Cast(ref typ, _) => Some(typ.clone()),
+        }
+    }
+
+    fn is_float(&self, vars: &HashMap<String, Variable>) -> bool {
+        self.typ(vars).map_or(false, |typ| typ.is_float())
+    }
+
+    /// Whether the term needs Java's long arithmetic, i.e. involves a value
+    /// that does not fit into an int.
+    fn is_long(&self, vars: &HashMap<String, Variable>) -> bool {
+        match *self {
+            Term::Variable(ref name) => !Type::SInt32.contains(&vars[name].typ),
+            Term::Constant(val) => val < i32::MIN as i128 || val > i32::MAX as i128,
+            Term::Float(_) => false,
+            Term::Negate(ref t) => t.is_long(vars),
+            Term::Binary(ref lhs, _, ref rhs) => lhs.is_long(vars) || rhs.is_long(vars),
+            Term::Cast(ref typ, ref t) => !Type::SInt32.contains(typ) || t.is_long(vars),
+        }
+    }
+
+    /// Removes the casts that cannot change the value of their operand, i.e.
+    /// widening ones, and folds casts of constants. What remains are the
+    /// narrowing casts that truncate their operand.
+    fn simplify_casts(&self, vars: &HashMap<String, Variable>) -> Term {
+        match *self {
+            Term::Variable(_) | Term::Constant(_) | Term::Float(_) => self.clone(),
+            Term::Negate(ref t) => Term::Negate(Box::new(t.simplify_casts(vars))),
+            Term::Binary(ref lhs, op, ref rhs) => Term::Binary(
+                Box::new(lhs.simplify_casts(vars)),
+                op,
+                Box::new(rhs.simplify_casts(vars)),
+            ),
+            Term::Cast(ref typ, ref t) => match t.simplify_casts(vars) {
+                Term::Constant(val) if typ.is_float() => typ.convert(val as f64),
+                Term::Constant(val) => Term::Constant(typ.wrap(val)),
+                Term::Float(val) => typ.convert(val),
+                ref t if t.typ(vars).map_or(false, |ref t| typ.contains(t)) => t.clone(),
+                t => Term::Cast(typ.clone(), Box::new(t)),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Term::Variable(ref name) => write!(f, "{}", name),
+            Term::Constant(val) => write!(f, "{}", val),
+            Term::Float(val) => write!(f, "{}", Type::Float64.float_literal(val)),
+            Term::Negate(ref t) => if t.precedence() < 8 {
+                write!(f, "-({})", t)
+            } else {
+                write!(f, "-{}", t)
+            },
+            Term::Binary(ref lhs, op, ref rhs) => {
+                if lhs.precedence() < op.precedence() {
+                    write!(f, "({})", lhs)?;
+                } else {
+                    write!(f, "{}", lhs)?;
+                }
+                if rhs.precedence() <= op.precedence() {
+                    write!(f, " {} ({})", op, rhs)
+                } else {
+                    write!(f, " {} {}", op, rhs)
+                }
+            }
+            Term::Cast(Type::SInt64, ref t) => write!(f, "{}", t),
+            Term::Cast(ref typ, ref t) => if t.precedence() < 8 {
+                write!(f, "({}) ({})", typ, t)
+            } else {
+                write!(f, "({}) {}", typ, t)
+            },
+        }
+    }
+}
+
+impl Relation {
+    /// The relation that holds exactly when this one does not. The comparator
+    /// is only negated when neither side can be NaN.
+    pub fn negation(&self, vars: &HashMap<String, Variable>) -> Relation {
+        if self.negated || self.lhs.is_float(vars) || self.rhs.is_float(vars) {
+            return Relation {
+                negated: !self.negated,
+                ..self.clone()
+            };
+        }
+        Relation {
+            comparator: self.comparator.negate(),
+            ..self.clone()
+        }
+    }
+
+    /// The relation as `x - y <comparator> c` for two variables x and y, if
+    /// it has that form and neither side can wrap around.
+    pub fn as_difference(
+        &self,
+        vars: &HashMap<String, Variable>,
+    ) -> Option<(String, String, Comparator, i128)> {
+        if self.negated {
+            return None;
+        }
+        // x - y + k <comparator> 0
+        let long = self.lhs.is_long(vars) || self.rhs.is_long(vars);
+        bounds(&self.lhs, vars, long)?;
+        bounds(&self.rhs, vars, long)?;
+        let linear = linearize(&self.lhs, long)?.add(linearize(&self.rhs, long)?, -1)?;
+        let mut coefficients = linear.coefficients.into_iter();
+        match (coefficients.next(), coefficients.next(), coefficients.next()) {
+            (Some((x, 1)), Some((y, -1)), None) | (Some((y, -1)), Some((x, 1)), None) => {
+                Some((x, y, self.comparator, linear.constant.checked_neg()?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Recognizes bit manipulation idioms, such as `(x & 1) == 0` for "x is
+    /// even", and describes them in words.
+    fn idiom(&self) -> Option<String> {
+        let (term, comparator, val) = match (&self.lhs, &self.rhs) {
+            (&Term::Binary(..), &Term::Constant(val)) => (&self.lhs, self.comparator, val),
+            (&Term::Constant(val), &Term::Binary(..)) => (&self.rhs, self.comparator.flip(), val),
+            _ => return None,
+        };
+        let holds = match (comparator, val) {
+            (Comparator::Eq, 0) | (Comparator::Neq, 1) => true,
+            (Comparator::Neq, 0) | (Comparator::Eq, 1) => false,
+            _ => return None,
+        };
+        match *term {
+            Term::Binary(ref x, Operator::BitAnd, ref mask) => match (&**x, &**mask) {
+                (&Term::Variable(ref name), &Term::Constant(1))
+                | (&Term::Constant(1), &Term::Variable(ref name)) => {
+                    Some(format!("{} is {}", name, if holds { "even" } else { "odd" }))
+                }
+                // x & (x - 1) clears the lowest set bit of x
+                (&Term::Variable(ref name), &Term::Binary(ref y, Operator::Sub, ref one))
+                    if val == 0 && **y == Term::Variable(name.clone())
+                        && **one == Term::Constant(1) =>
+                {
+                    // the minimum value has a single bit set, the sign bit
+                    Some(format!(
+                        "{} is {}",
+                        name,
+                        if holds {
+                            "zero, a power of two or the minimum value of its type"
+                        } else {
+                            "neither zero, a power of two nor the minimum value of its type"
+                        }
+                    ))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negated {
+            let relation = Relation {
+                negated: false,
+                ..self.clone()
+            };
+            return write!(f, "!({})", relation);
+        }
+        if let Some(s) = self.idiom() {
+            return write!(f, "{}", s);
+        }
+        // bitwise operators bind looser than comparisons in Java
+        for (i, term) in [&self.lhs, &self.rhs].iter().enumerate() {
+            if i == 1 {
+                write!(f, " {} ", self.comparator)?;
+            }
+            if term.precedence() <= Operator::BitAnd.precedence() {
+                write!(f, "({})", term)?;
+            } else {
+                write!(f, "{}", term)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -65,14 +715,22 @@ impl Expression {
 #[derive(Debug, PartialEq)]
 enum Ast {
     And(Vec<Ast>),
-    Lt(String, i64),
-    Lte(String, i64),
-    Gt(String, i64),
-    Gte(String, i64),
-    Eq(String, i64),
-    Neq(String, i64),
+    Or(Vec<Ast>),
+    Not(Box<Ast>),
+    Compare(Term, Comparator, Term),
 }
 
+/// A comparison appearing in the disjunctive normal form of a condition, and
+/// whether it is negated.
+type Literal<'a> = (&'a Term, Comparator, &'a Term, bool);
+
+/// The variable ranges and residual relations of one conjunction.
+pub type Conjunct = (HashMap<String, Variable>, Vec<Relation>);
+
+/// Upper bound on the number of conjunctions produced when converting a
+/// condition into disjunctive normal form.
+const MAX_DISJUNCTS: usize = 256;
+
 fn variable_map(var_decls: Vec<(&[u8], Type)>) -> HashMap<String, Variable> {
     let mut vars = HashMap::new();
     for &(ref name, ref typ) in var_decls.iter() {
@@ -82,7 +740,8 @@ fn variable_map(var_decls: Vec<(&[u8], Type)>) -> HashMap<String, Variable> {
             Variable {
                 name: name_string,
                 typ: typ.clone(),
-                range: typ.range(),
+                range: typ.domain(),
+                approximate: false,
             },
         );
     }
@@ -91,109 +750,940 @@ fn variable_map(var_decls: Vec<(&[u8], Type)>) -> HashMap<String, Variable> {
 
 fn bytes_to_type(bytes: &[u8]) -> Type {
     match bytes {
+        b"bool" => Type::Bool,
         b"sint8" => Type::SInt8,
         b"sint16" => Type::SInt16,
         b"sint32" => Type::SInt32,
         b"sint64" => Type::SInt64,
+        b"uint8" => Type::UInt8,
+        b"uint16" => Type::UInt16,
+        b"uint32" => Type::UInt32,
+        b"uint64" => Type::UInt64,
+        b"float" => Type::Float32,
+        b"double" => Type::Float64,
         _ => unreachable!(),
     }
 }
 
-fn make_comparison(name: &[u8], op: &[u8], val: i64) -> Ast {
-    let name_string = String::from_utf8(name.to_vec()).unwrap();
-    match op {
-        b"==" => Ast::Eq(name_string, val),
-        b"!=" => Ast::Neq(name_string, val),
-        b"<" => Ast::Lt(name_string, val),
-        b"<=" => Ast::Lte(name_string, val),
-        b">" => Ast::Gt(name_string, val),
-        b">=" => Ast::Gte(name_string, val),
-        _ => unreachable!(),
-    }
+fn fold_terms(first: Term, rest: Vec<(&[u8], Term)>) -> Term {
+    rest.into_iter().fold(first, |lhs, (op, rhs)| {
+        let op = match op {
+            b"+" => Operator::Add,
+            b"-" => Operator::Sub,
+            b"*" => Operator::Mul,
+            b"/" => Operator::Div,
+            b"%" => Operator::Rem,
+            b"&" => Operator::BitAnd,
+            b"|" => Operator::BitOr,
+            b"^" => Operator::BitXor,
+            b"<<" => Operator::Shl,
+            b">>" => Operator::Shr,
+            b">>>" => Operator::UShr,
+            _ => unreachable!(),
+        };
+        Term::Binary(Box::new(lhs), op, Box::new(rhs))
+    })
+}
+
+fn make_comparison(lhs: Term, op: &[u8], rhs: Term) -> Ast {
+    let comparator = match op {
+        b"==" => Comparator::Eq,
+        b"!=" => Comparator::Neq,
+        b"<" => Comparator::Lt,
+        b"<=" => Comparator::Lte,
+        b">" => Comparator::Gt,
+        b">=" => Comparator::Gte,
+        _ => unreachable!(),
+    };
+    Ast::Compare(lhs, comparator, rhs)
+}
+
+/// A linear combination of variables with a constant offset. Arithmetic is
+/// done on i128 so that normalizing 64-bit constraints cannot overflow.
+#[derive(Debug, PartialEq)]
+struct Linear {
+    coefficients: BTreeMap<String, i128>,
+    constant: i128,
+}
+
+impl Linear {
+    fn constant(val: i128) -> Linear {
+        Linear {
+            coefficients: BTreeMap::new(),
+            constant: val,
+        }
+    }
+
+    fn variable(name: &str) -> Linear {
+        let mut coefficients = BTreeMap::new();
+        coefficients.insert(String::from(name), 1);
+        Linear {
+            coefficients,
+            constant: 0,
+        }
+    }
+
+    fn as_constant(&self) -> Option<i128> {
+        if self.coefficients.len() == 0 {
+            Some(self.constant)
+        } else {
+            None
+        }
+    }
+
+    /// self + factor * other
+    fn add(mut self, other: Linear, factor: i128) -> Option<Linear> {
+        for (name, c) in other.coefficients.into_iter() {
+            let sum = self.coefficients
+                .get(&name)
+                .cloned()
+                .unwrap_or(0)
+                .checked_add(c.checked_mul(factor)?)?;
+            if sum == 0 {
+                self.coefficients.remove(&name);
+            } else {
+                self.coefficients.insert(name, sum);
+            }
+        }
+        self.constant = self.constant
+            .checked_add(other.constant.checked_mul(factor)?)?;
+        Some(self)
+    }
+
+    fn scale(mut self, factor: i128) -> Option<Linear> {
+        if factor == 0 {
+            return Some(Linear::constant(0));
+        }
+        for (_, c) in self.coefficients.iter_mut() {
+            *c = c.checked_mul(factor)?;
+        }
+        self.constant = self.constant.checked_mul(factor)?;
+        Some(self)
+    }
+}
+
+/// Evaluates `op` on two constants with Java's long arithmetic if `long` is
+/// set and with its int arithmetic otherwise.
+fn eval_constants(op: Operator, lhs: i128, rhs: i128, long: bool) -> Option<i128> {
+    if long {
+        if lhs < i64::MIN as i128 || lhs > i64::MAX as i128 || rhs < i64::MIN as i128
+            || rhs > i64::MAX as i128
+        {
+            return None;
+        }
+        op.eval_long(lhs as i64, rhs as i64).map(|val| val as i128)
+    } else {
+        op.eval_int(lhs as i32, rhs as i32).map(|val| val as i128)
+    }
+}
+
+/// Brings a term into linear form, or returns None if it is not linear, e.g.
+/// a product of two variables or a division by a variable. Constants are
+/// folded with Java's long arithmetic if `long` is set and with its int
+/// arithmetic otherwise.
+fn linearize(term: &Term, long: bool) -> Option<Linear> {
+    match *term {
+        Term::Variable(ref name) => Some(Linear::variable(name)),
+        Term::Constant(val) => Some(Linear::constant(val)),
+        Term::Float(_) => None,
+        Term::Negate(ref t) => match linearize(t, long)? {
+            ref linear if linear.as_constant().is_some() => {
+                let val = eval_constants(Operator::Sub, 0, linear.constant, long)?;
+                Some(Linear::constant(val))
+            }
+            linear => linear.scale(-1),
+        },
+        // narrowing casts wrap around
+        Term::Cast(..) => None,
+        Term::Binary(ref lhs, op, ref rhs) => {
+            let lhs = linearize(lhs, long)?;
+            let rhs = linearize(rhs, long)?;
+            if let (Some(l), Some(r)) = (lhs.as_constant(), rhs.as_constant()) {
+                return eval_constants(op, l, r, long).map(Linear::constant);
+            }
+            match op {
+                Operator::Add => lhs.add(rhs, 1),
+                Operator::Sub => lhs.add(rhs, -1),
+                Operator::Mul => match (lhs.as_constant(), rhs.as_constant()) {
+                    (Some(c), _) => rhs.scale(c),
+                    (_, Some(c)) => lhs.scale(c),
+                    _ => None,
+                },
+                // shifting left by a constant is a multiplication, where Java
+                // only uses the lowest five bits of the distance for ints
+                Operator::Shl if rhs.as_constant().is_some() => {
+                    let mask = if long { 0x3f } else { 0x1f };
+                    lhs.scale(1 << (rhs.as_constant()? & mask))
+                }
+                // everything else is only linear when applied to constants
+                _ => None,
+            }
+        }
+    }
+}
+
+/// The smallest and largest value of a linear term over the ranges of its
+/// variables, or None if evaluating its arithmetic may wrap around in Java's long
+/// arithmetic if `long` is set and its int arithmetic otherwise, in which case
+/// solving it in exact arithmetic is unsound.
+fn bounds(term: &Term, vars: &HashMap<String, Variable>, long: bool) -> Option<(i128, i128)> {
+    let (min, max) = if long {
+        (i64::MIN as i128, i64::MAX as i128)
+    } else {
+        (i32::MIN as i128, i32::MAX as i128)
+    };
+    let (lower, upper) = match *term {
+        Term::Variable(ref name) => {
+            let ranges = vars[name].range.as_integer()?.get_ranges();
+            return Some((ranges.first()?.0, ranges.last()?.1));
+        }
+        Term::Constant(val) => return Some((val, val)),
+        Term::Negate(ref t) => match bounds(t, vars, long)? {
+            (l, u) if l == u => {
+                let val = eval_constants(Operator::Sub, 0, l, long)?;
+                (val, val)
+            }
+            (l, u) => (-u, -l),
+        },
+        Term::Binary(ref lhs, op, ref rhs) => {
+            let (a, b) = (bounds(lhs, vars, long)?, bounds(rhs, vars, long)?);
+            // constants are folded the way Java does
+            if a.0 == a.1 && b.0 == b.1 {
+                let val = eval_constants(op, a.0, b.0, long)?;
+                return Some((val, val));
+            }
+            match op {
+                Operator::Add => (a.0 + b.0, a.1 + b.1),
+                Operator::Sub => (a.0 - b.1, a.1 - b.0),
+                Operator::Mul => {
+                    let products = [a.0 * b.0, a.0 * b.1, a.1 * b.0, a.1 * b.1];
+                    (*products.iter().min()?, *products.iter().max()?)
+                }
+                Operator::Shl if b.0 == b.1 => {
+                    let mask = if long { 0x3f } else { 0x1f };
+                    let factor = 1 << (b.0 & mask);
+                    (a.0 * factor, a.1 * factor)
+                }
+                _ => return None,
+            }
+        }
+        Term::Float(_) | Term::Cast(..) => return None,
+    };
+    if lower < min || upper > max {
+        None
+    } else {
+        Some((lower, upper))
+    }
+}
+
+fn div_floor(a: i128, b: i128) -> i128 {
+    let q = a / b;
+    if a % b != 0 && (a < 0) != (b < 0) {
+        q - 1
+    } else {
+        q
+    }
+}
+
+fn div_ceil(a: i128, b: i128) -> i128 {
+    let q = a / b;
+    if a % b != 0 && (a < 0) == (b < 0) {
+        q + 1
+    } else {
+        q
+    }
+}
+
+/// The values of x satisfying `coefficient * x <comparator> bound`, or None if
+/// every value does.
+fn allowed_values(comparator: Comparator, coefficient: i128, bound: i128) -> Option<Range> {
+    let (comparator, a, k) = if coefficient < 0 {
+        (comparator.flip(), -coefficient, bound.saturating_neg())
+    } else {
+        (comparator, coefficient, bound)
+    };
+    // the bounds saturate, which is harmless as no type comes close to the
+    // limits of i128
+    Some(match comparator {
+        Comparator::Lt => Range::from(i128::MIN, div_ceil(k, a).saturating_sub(1)),
+        Comparator::Lte => Range::from(i128::MIN, div_floor(k, a)),
+        Comparator::Gt => Range::from(div_floor(k, a).saturating_add(1), i128::MAX),
+        Comparator::Gte => Range::from(div_ceil(k, a), i128::MAX),
+        Comparator::Eq => if k % a == 0 {
+            Range::from(k / a, k / a)
+        } else {
+            Range::from(1, 0)
+        },
+        Comparator::Neq => if k % a == 0 {
+            Range::from(i128::MIN, (k / a).saturating_sub(1))
+                .union(&Range::from((k / a).saturating_add(1), i128::MAX))
+        } else {
+            return None;
+        },
+    })
+}
+
+/// Restricts the range of `var` to the values of x satisfying
+/// `coefficient * x <comparator> bound`.
+fn restrict(var: &mut Variable, comparator: Comparator, coefficient: i128, bound: i128) {
+    if let Some(allowed) = allowed_values(comparator, coefficient, bound) {
+        var.range = var.range.intersect(&Domain::Integer(allowed));
+    }
+}
+
+/// Upper bound on the number of intervals used to represent the values
+/// allowed by a comparison on a narrowing cast.
+const MAX_CAST_INTERVALS: i128 = 1024;
+
+/// Restricts the range of `var` to the values of x for which the narrowing
+/// cast `(typ) x` satisfies `<comparator> bound`. The cast keeps the lowest
+/// bits of x, so the allowed values of x repeat every 2^width(typ). If there
+/// are too many repetitions to represent, the range is left as is and marked
+/// as approximate.
+fn restrict_cast(var: &mut Variable, typ: &Type, comparator: Comparator, bound: i128) {
+    let (lower, upper) = {
+        let ranges = var.range.as_integer().unwrap().get_ranges();
+        match (ranges.first(), ranges.last()) {
+            (Some(&(l, _)), Some(&(_, u))) => (l, u),
+            _ => return,
+        }
+    };
+    let narrow = match allowed_values(comparator, 1, bound) {
+        Some(allowed) => typ.range().intersect(&allowed),
+        None => typ.range(),
+    };
+    let modulus = 1i128 << typ.width();
+    let min = typ.range().get_ranges()[0].0;
+    let first = div_floor(lower - min, modulus);
+    let last = div_floor(upper - min, modulus);
+    if (last - first + 1) * narrow.get_ranges().len() as i128 > MAX_CAST_INTERVALS {
+        var.approximate = true;
+        return;
+    }
+    let mut allowed = Range::from(1, 0);
+    for n in first..last + 1 {
+        for &(l, u) in narrow.get_ranges().iter() {
+            allowed = allowed.union(&Range::from(l + n * modulus, u + n * modulus));
+        }
+    }
+    var.range = var.range.intersect(&Domain::Integer(allowed));
+}
+
+/// Matches a comparison between a narrowing cast of a variable and a
+/// constant, e.g. `(byte) b != 2`.
+fn cast_comparison<'a>(
+    lhs: &'a Term,
+    comparator: Comparator,
+    rhs: &'a Term,
+    long: bool,
+) -> Option<(&'a str, &'a Type, Comparator, i128)> {
+    match (lhs, rhs) {
+        (&Term::Cast(ref typ, ref t), _) => match **t {
+            Term::Variable(ref name) => {
+                Some((name, typ, comparator, linearize(rhs, long)?.as_constant()?))
+            }
+            _ => None,
+        },
+        (_, &Term::Cast(..)) => cast_comparison(rhs, comparator.flip(), lhs, long),
+        _ => None,
+    }
+}
+
+/// Matches a comparison between the remainder of a variable and a constant,
+/// e.g. `n % 4 == 0`, returning the divisor and the remainder.
+fn remainder_comparison<'a>(
+    lhs: &'a Term,
+    comparator: Comparator,
+    rhs: &'a Term,
+) -> Option<(&'a str, Comparator, i128, i128)> {
+    match (lhs, rhs) {
+        (&Term::Binary(ref x, Operator::Rem, ref divisor), &Term::Constant(val)) => {
+            match (&**x, &**divisor) {
+                (&Term::Variable(ref name), &Term::Constant(divisor)) if divisor != 0 => {
+                    Some((name, comparator, divisor, val))
+                }
+                _ => None,
+            }
+        }
+        (&Term::Constant(_), &Term::Binary(..)) => remainder_comparison(rhs, comparator.flip(), lhs),
+        _ => None,
+    }
+}
+
+/// The values of x for which `x % divisor == remainder` in Java, where the
+/// remainder takes the sign of x.
+fn remainder_values(divisor: i128, remainder: i128) -> Range {
+    let modulus = divisor.abs();
+    if remainder.abs() >= modulus {
+        return Range::from(1, 0);
+    }
+    let values = Range::congruent(modulus, remainder);
+    if remainder > 0 {
+        values.intersect(&Range::from(1, i128::MAX))
+    } else if remainder < 0 {
+        values.intersect(&Range::from(i128::MIN, -1))
+    } else {
+        values
+    }
+}
+
+/// Restricts the range of `var` to the values of x satisfying
+/// `x % divisor <comparator> remainder`. Returns false if the values cannot be
+/// represented by a congruence.
+fn restrict_remainder(
+    var: &mut Variable,
+    comparator: Comparator,
+    divisor: i128,
+    remainder: i128,
+) -> bool {
+    let values = remainder_values(divisor, remainder);
+    let allowed = match comparator {
+        Comparator::Eq => values,
+        // the remaining values form a single congruence class
+        Comparator::Neq if remainder == 0 && divisor.abs() == 1 => Range::from(1, 0),
+        Comparator::Neq if remainder == 0 && divisor.abs() == 2 => Range::congruent(2, 1),
+        Comparator::Neq if values.is_empty() => return true,
+        _ => return false,
+    };
+    var.range = var.range.intersect(&Domain::Integer(allowed));
+    true
+}
+
+fn ast_variables<'a>(ast: &'a Ast, names: &mut Vec<&'a str>) {
+    match *ast {
+        Ast::And(ref v) | Ast::Or(ref v) => for e in v.iter() {
+            ast_variables(e, names);
+        },
+        Ast::Not(ref e) => ast_variables(e, names),
+        Ast::Compare(ref lhs, _, ref rhs) => {
+            lhs.variables(names);
+            rhs.variables(names);
+        }
+    }
+}
+
+/// Converts `ast` (or its negation) into disjunctive normal form, pushing
+/// negations down to the comparisons. Returns None if the result would have
+/// more than `MAX_DISJUNCTS` conjunctions.
+fn to_dnf<'a>(ast: &'a Ast, negated: bool) -> Option<Vec<Vec<Literal<'a>>>> {
+    match (ast, negated) {
+        (&Ast::Compare(ref lhs, comparator, ref rhs), _) => {
+            Some(vec![vec![(lhs, comparator, rhs, negated)]])
+        }
+        (&Ast::Not(ref e), _) => to_dnf(e, !negated),
+        (&Ast::Or(ref v), false) | (&Ast::And(ref v), true) => {
+            let mut disjuncts = Vec::new();
+            for e in v.iter() {
+                disjuncts.extend(to_dnf(e, negated)?);
+                if disjuncts.len() > MAX_DISJUNCTS {
+                    return None;
+                }
+            }
+            Some(disjuncts)
+        }
+        (&Ast::And(ref v), false) | (&Ast::Or(ref v), true) => {
+            let mut disjuncts = vec![Vec::new()];
+            for e in v.iter() {
+                let rhs = to_dnf(e, negated)?;
+                if disjuncts.len() * rhs.len() > MAX_DISJUNCTS {
+                    return None;
+                }
+                disjuncts = disjuncts
+                    .iter()
+                    .flat_map(|l| {
+                        rhs.iter().map(move |r| {
+                            let mut conjunction = l.clone();
+                            conjunction.extend(r.iter().cloned());
+                            conjunction
+                        })
+                    })
+                    .collect();
+            }
+            Some(disjuncts)
+        }
+    }
+}
+
+/// The floating point values of x satisfying `x <comparator> bound`.
+fn float_values(comparator: Comparator, bound: f64) -> FloatRange {
+    if bound.is_nan() {
+        // every comparison with NaN is false, except for !=
+        return if comparator == Comparator::Neq {
+            FloatRange::full()
+        } else {
+            FloatRange::empty()
+        };
+    }
+    let min = Bound::closed(f64::NEG_INFINITY);
+    let max = Bound::closed(f64::INFINITY);
+    match comparator {
+        Comparator::Lt => FloatRange::from(min, Bound::open(bound)),
+        Comparator::Lte => FloatRange::from(min, Bound::closed(bound)),
+        Comparator::Gt => FloatRange::from(Bound::open(bound), max),
+        Comparator::Gte => FloatRange::from(Bound::closed(bound), max),
+        Comparator::Eq => FloatRange::from(Bound::closed(bound), Bound::closed(bound)),
+        Comparator::Neq => {
+            FloatRange::from(Bound::closed(bound), Bound::closed(bound)).complement()
+        }
+    }
+}
+
+/// The integers among `values`.
+fn integers_in(values: &FloatRange) -> Range {
+    let mut integers = Range::from(1, 0);
+    for &(lower, upper) in values.get_intervals().iter() {
+        // conversions saturate, so the infinities become the bounds of i128
+        let l = lower.value.ceil();
+        let l = if l == lower.value && !lower.closed {
+            (l as i128).saturating_add(1)
+        } else {
+            l as i128
+        };
+        let u = upper.value.floor();
+        let u = if u == upper.value && !upper.closed {
+            (u as i128).saturating_sub(1)
+        } else {
+            u as i128
+        };
+        integers = integers.union(&Range::from(l, u));
+    }
+    integers
+}
+
+/// Evaluates a constant term of a floating point comparison.
+fn float_constant(term: &Term) -> Option<f64> {
+    match *term {
+        Term::Constant(val) => Some(val as f64),
+        Term::Float(val) => Some(val),
+        Term::Negate(ref t) => float_constant(t).map(|val| -val),
+        _ => None,
+    }
+}
+
+/// Adds the floating point comparison `lhs <comparator> rhs`, or its negation,
+/// to a conjunction. Unlike for integers, the negation is not a comparison
+/// itself, as every comparison with NaN is false.
+fn apply_float(
+    vars: &mut HashMap<String, Variable>,
+    relations: &mut Vec<Relation>,
+    (lhs, comparator, rhs, negated): (Term, Comparator, Term, bool),
+) -> bool {
+    let comparison = match (&lhs, float_constant(&lhs), &rhs, float_constant(&rhs)) {
+        (_, Some(l), _, Some(r)) => return comparator.holds_float(l, r) != negated,
+        (&Term::Variable(ref name), _, _, Some(bound)) => Some((name, comparator, bound)),
+        (_, Some(bound), &Term::Variable(ref name), _) => Some((name, comparator.flip(), bound)),
+        _ => None,
+    };
+    match comparison {
+        Some((name, comparator, bound)) => {
+            let mut allowed = float_values(comparator, bound);
+            if negated {
+                allowed = allowed.complement();
+            }
+            let var = vars.get_mut(name).unwrap();
+            // an integer variable compared to a floating point constant
+            let allowed = match var.range {
+                Domain::Integer(_) => Domain::Integer(integers_in(&allowed)),
+                Domain::Float(_) => Domain::Float(allowed),
+            };
+            var.range = var.range.intersect(&allowed);
+            !var.range.is_empty()
+        }
+        None => {
+            relations.push(Relation {
+                lhs,
+                comparator,
+                rhs,
+                negated,
+            });
+            true
+        }
+    }
+}
+
+/// Adds the constraint `lhs <comparator> rhs`, or its negation, to a
+/// conjunction, returning false if it is trivially unsatisfiable.
+fn apply(
+    vars: &mut HashMap<String, Variable>,
+    relations: &mut Vec<Relation>,
+    (lhs, comparator, rhs, negated): Literal,
+) -> bool {
+    // widening casts to long, which are simplified away, select long arithmetic
+    let long = lhs.is_long(vars) || rhs.is_long(vars);
+    let lhs = lhs.simplify_casts(vars);
+    let rhs = rhs.simplify_casts(vars);
+    if lhs.is_float(vars) || rhs.is_float(vars) {
+        return apply_float(vars, relations, (lhs, comparator, rhs, negated));
+    }
+    // comparisons of integers are total, so their negation is a comparison
+    let comparator = if negated {
+        comparator.negate()
+    } else {
+        comparator
+    };
+    // lhs <comparator> rhs  <=>  lhs - rhs <comparator> 0, unless either side
+    // may wrap around, e.g. `a + 1` for the largest a
+    let linear = match (bounds(&lhs, vars, long), bounds(&rhs, vars, long)) {
+        (Some(_), Some(_)) => linearize(&lhs, long).and_then(|l| l.add(linearize(&rhs, long)?, -1)),
+        _ => None,
+    };
+    match linear {
+        Some(ref linear) if linear.coefficients.len() == 0 => {
+            return comparator.holds(linear.constant, 0);
+        }
+        Some(ref linear) if linear.coefficients.len() == 1 => {
+            let (name, &coefficient) = linear.coefficients.iter().next().unwrap();
+            let var = vars.get_mut(name).unwrap();
+            restrict(var, comparator, coefficient, linear.constant.saturating_neg());
+            return !var.range.is_empty();
+        }
+        _ => {
+            if let Some((name, comparator, divisor, remainder)) =
+                remainder_comparison(&lhs, comparator, &rhs)
+            {
+                let var = vars.get_mut(name).unwrap();
+                if restrict_remainder(var, comparator, divisor, remainder) {
+                    return !var.range.is_empty();
+                }
+            }
+            if let Some((name, typ, comparator, bound)) = cast_comparison(&lhs, comparator, &rhs, long) {
+                let var = vars.get_mut(name).unwrap();
+                // casts of floating point variables are kept as relations
+                if !var.typ.is_float() {
+                    restrict_cast(var, typ, comparator, bound);
+                    return !var.range.is_empty();
+                }
+            }
+            relations.push(Relation {
+                lhs,
+                comparator,
+                rhs,
+                negated: false,
+            })
+        }
+    }
+    true
+}
+
+/// Whether every assignment satisfying `a` satisfies `b`, judging by the
+/// ranges of the variables and the relations alone.
+fn implies(a: &Conjunct, b: &Conjunct) -> bool {
+    b.1.iter().all(|relation| a.1.contains(relation)) && a.0.iter().all(|(name, var)| {
+        b.0
+            .get(name)
+            .map_or(false, |other| var.range.intersect(&other.range) == var.range)
+    })
+}
+
+/// Merges two conjunctions into one that holds exactly when either does, if
+/// one implies the other or they differ only in the range of one variable.
+fn merge_conjuncts(a: &Conjunct, b: &Conjunct) -> Option<Conjunct> {
+    if a.0.len() != b.0.len() || a.0.keys().any(|name| !b.0.contains_key(name)) {
+        return None;
+    }
+    let implied = if implies(a, b) {
+        Some(b)
+    } else if implies(b, a) {
+        Some(a)
+    } else {
+        None
+    };
+    if let Some(weaker) = implied {
+        let mut merged = weaker.clone();
+        for (name, var) in merged.0.iter_mut() {
+            var.approximate |= a.0[name].approximate || b.0[name].approximate;
+        }
+        return Some(merged);
+    }
+    if a.1 != b.1 {
+        return None;
+    }
+    let mut differing = None;
+    for (name, var) in a.0.iter() {
+        if b.0[name].range != var.range {
+            if differing.is_some() {
+                return None;
+            }
+            differing = Some(name);
+        }
+    }
+    let mut merged = a.clone();
+    for (name, var) in merged.0.iter_mut() {
+        var.approximate |= b.0[name].approximate;
+    }
+    if let Some(name) = differing {
+        let range = a.0[name].range.exact_union(&b.0[name].range)?;
+        merged.0.get_mut(name).unwrap().range = range;
+    }
+    Some(merged)
+}
+
+/// Merges the conjunctions of a disjunction while the result stays equivalent,
+/// i.e. drops the ones implied by others and unions the ranges of the ones
+/// differing in a single variable.
+pub fn merge_disjunction(conjuncts: &mut Vec<Conjunct>) {
+    let mut i = 0;
+    while i < conjuncts.len() {
+        let mut j = i + 1;
+        while j < conjuncts.len() {
+            if let Some(merged) = merge_conjuncts(&conjuncts[i], &conjuncts[j]) {
+                conjuncts[i] = merged;
+                conjuncts.remove(j);
+                j = i + 1;
+            } else {
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+}
+
+fn interprete(vars: HashMap<String, Variable>, ast: &Ast) -> Option<Condition> {
+    let mut names = Vec::new();
+    ast_variables(ast, &mut names);
+    if names.iter().any(|name| !vars.contains_key(*name)) {
+        return None;
+    }
+    let mut conjuncts: Vec<Conjunct> = Vec::new();
+    for mut conjunction in to_dnf(ast, false)?.into_iter() {
+        // restrict the variables as much as possible before looking at
+        // narrowing casts, whose precision depends on the width of the range,
+        // and at arithmetic, which can only be solved if it cannot wrap around
+        conjunction.sort_by_key(|&(lhs, _, rhs, _)| {
+            let arithmetic = |t: &Term| match *t {
+                Term::Binary(..) | Term::Negate(_) => true,
+                _ => false,
+            };
+            (lhs.has_cast() || rhs.has_cast(), arithmetic(lhs) || arithmetic(rhs))
+        });
+        let mut conjunct = (vars.clone(), Vec::new());
+        if conjunction
+            .into_iter()
+            .all(|literal| apply(&mut conjunct.0, &mut conjunct.1, literal))
+        {
+            conjuncts.push(conjunct);
+        }
+    }
+    if conjuncts.len() == 0 {
+        // no value satisfies the condition
+        let mut unsatisfiable = vars;
+        for (_, var) in unsatisfiable.iter_mut() {
+            var.range = match var.range {
+                Domain::Integer(_) => Domain::Integer(Range::from(1, 0)),
+                Domain::Float(_) => Domain::Float(FloatRange::empty()),
+            };
+        }
+        return Some(Condition::Conditions(unsatisfiable, Vec::new()));
+    }
+    // build the range of a variable from the union of the disjuncts
+    merge_disjunction(&mut conjuncts);
+    match conjuncts.len() {
+        1 => conjuncts
+            .pop()
+            .map(|(vars, relations)| Condition::Conditions(vars, relations)),
+        _ => Some(Condition::Disjunction(conjuncts
+            .into_iter()
+            .map(|(vars, relations)| Condition::Conditions(vars, relations))
+            .collect())),
+    }
+}
+
+// names may refer to fields, array elements and array lengths, e.g. `xs[0]`
+named! {
+    parse_variable,
+    delimited!(
+        tag!("'"),
+        is_a_s!("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789._$[]"),
+        tag!("'")
+    )
+}
+
+named! {
+    parse_parentheses<Ast>,
+    delimited!(tag!("("), alt_complete!(parse_comparision | parse_or), tag!(")"))
+}
+
+named! {
+    parse_bool_literal<Term>,
+    alt_complete!(value!(Term::Constant(1), tag!("true")) | value!(Term::Constant(0), tag!("false")))
+}
+
+// a boolean variable or literal used as a condition, e.g. `'flag'`
+named! {
+    parse_boolean<Ast>,
+    map!(
+        alt_complete!(
+            map!(parse_variable, |name| Term::Variable(String::from_utf8(name.to_vec()).unwrap())) |
+            parse_bool_literal
+        ),
+        |t| Ast::Compare(t, Comparator::Eq, Term::Constant(1))
+    )
+}
+
+named! {
+    parse_negation<Ast>,
+    alt_complete!(
+        map!(preceded!(tag!("!"), parse_negation), |ast| Ast::Not(Box::new(ast))) |
+        parse_parentheses |
+        parse_boolean
+    )
+}
+
+named! {
+    parse_and<Ast>,
+    map!(separated_list_complete!(ws!(tag!("&&")), parse_negation), Ast::And)
+}
+
+named! {
+    parse_or<Ast>,
+    map!(
+        separated_nonempty_list_complete!(ws!(tag!("||")), parse_and),
+        |mut v: Vec<Ast>| if v.len() == 1 { v.pop().unwrap() } else { Ast::Or(v) }
+    )
+}
+
+named! {
+    parse_negative_number<i128>,
+    map_res!(
+        recognize!(do_parse!(tag!("-") >> digit >> ())),
+        |num| str::from_utf8(num).unwrap().parse::<i128>()
+    )
+}
+
+named! {
+    parse_number<i128>,
+    alt_complete!(
+        map_res!(digit, |ds| str::from_utf8(ds).unwrap().parse::<i128>()) |
+        parse_negative_number
+    )
+}
+
+named! {
+    parse_float<f64>,
+    alt_complete!(
+        value!(f64::NAN, tag!("NaN")) |
+        value!(f64::INFINITY, tag!("Infinity")) |
+        value!(f64::NEG_INFINITY, tag!("-Infinity")) |
+        map_res!(
+            terminated!(
+                recognize!(do_parse!(
+                    opt!(complete!(tag!("-"))) >> digit >> tag!(".") >> digit >>
+                    opt!(complete!(do_parse!(
+                        one_of!("eE") >> opt!(complete!(tag!("-"))) >> digit >> ()
+                    ))) >>
+                    ()
+                )),
+                opt!(complete!(one_of!("fFdD")))
+            ),
+            |num| str::from_utf8(num).unwrap().parse::<f64>()
+        )
+    )
+}
+
+named! {
+    parse_cast<Type>,
+    delimited!(tag!("("), parse_type, tag!(")"))
 }
 
-fn interprete(vars: &mut HashMap<String, Variable>, ast: &Ast) -> Option<()> {
-    match ast {
-        &Ast::And(ref v) => for e in v.iter() {
-            interprete(vars, e)?;
-        },
-        &Ast::Lt(ref name, val) => {
-            let mut v = vars.get_mut(name)?;
-            v.range = v.range.intersect(&Range::from(i64::MIN, val - 1));
-        }
-        &Ast::Lte(ref name, val) => {
-            let mut v = vars.get_mut(name)?;
-            v.range = v.range.intersect(&Range::from(i64::MIN, val));
-        }
-        &Ast::Gt(ref name, val) => {
-            let mut v = vars.get_mut(name)?;
-            v.range = v.range.intersect(&Range::from(val + 1, i64::MAX));
-        }
-        &Ast::Gte(ref name, val) => {
-            let mut v = vars.get_mut(name)?;
-            v.range = v.range.intersect(&Range::from(val, i64::MAX));
-        }
-        &Ast::Eq(ref name, val) => {
-            let mut v = vars.get_mut(name)?;
-            v.range = v.range.intersect(&Range::from(val, val));
-        }
-        &Ast::Neq(ref name, val) => {
-            let mut v = vars.get_mut(name)?;
-            v.range = v.range
-                .intersect(&Range::from(i64::MIN, val - 1).union(&Range::from(val + 1, i64::MAX)));
-        }
-    }
-    Some(())
+named! {
+    parse_atom<Term>,
+    alt_complete!(
+        map!(pair!(parse_cast, parse_atom), |(typ, t)| Term::Cast(typ, Box::new(t))) |
+        map!(parse_variable, |name| Term::Variable(String::from_utf8(name.to_vec()).unwrap())) |
+        map!(parse_float, Term::Float) |
+        parse_bool_literal |
+        map!(parse_number, Term::Constant) |
+        map!(preceded!(tag!("-"), parse_atom), |t| Term::Negate(Box::new(t))) |
+        delimited!(tag!("("), parse_term, tag!(")"))
+    )
 }
 
 named! {
-    parse_variable,
-    delimited!(tag!("'"), is_a_s!("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789."), tag!("'"))
+    parse_product<Term>,
+    do_parse!(
+        first: parse_atom >>
+        rest: many0!(complete!(pair!(
+            ws!(alt_complete!(tag!("*") | tag!("/") | tag!("%"))),
+            parse_atom
+        ))) >>
+        (fold_terms(first, rest))
+    )
 }
 
 named! {
-    parse_ident,
+    parse_sum<Term>,
     do_parse!(
-        opt!(delimited!(tag!("("), parse_type, tag!(")"))) >>
-        bytes: parse_variable >>
-        (bytes)
+        first: parse_product >>
+        rest: many0!(complete!(pair!(ws!(alt_complete!(tag!("+") | tag!("-"))), parse_product))) >>
+        (fold_terms(first, rest))
     )
 }
 
 named! {
-    parse_parentheses<Ast>,
-    delimited!(tag!("("), alt_complete!(parse_comparision | parse_and), tag!(")"))
+    parse_shift<Term>,
+    do_parse!(
+        first: parse_sum >>
+        rest: many0!(complete!(pair!(
+            ws!(alt_complete!(tag!("<<") | tag!(">>>") | tag!(">>"))),
+            parse_sum
+        ))) >>
+        (fold_terms(first, rest))
+    )
 }
 
 named! {
-    parse_and<Ast>,
-    map!(separated_list_complete!(ws!(tag!("&&")), parse_parentheses), Ast::And)
+    parse_bitwise_and<Term>,
+    do_parse!(
+        first: parse_shift >>
+        rest: many0!(complete!(pair!(
+            ws!(terminated!(tag!("&"), not!(tag!("&")))),
+            parse_shift
+        ))) >>
+        (fold_terms(first, rest))
+    )
 }
 
-named!{
-    parse_negative_number<i64>,
-    map!(
-        do_parse!(tag!("-") >> ds: digit >> (ds)),
-        |num| -str::from_utf8(num).unwrap().parse::<i64>().unwrap()
+named! {
+    parse_bitwise_xor<Term>,
+    do_parse!(
+        first: parse_bitwise_and >>
+        rest: many0!(complete!(pair!(ws!(tag!("^")), parse_bitwise_and))) >>
+        (fold_terms(first, rest))
+    )
+}
+
+named! {
+    parse_term<Term>,
+    do_parse!(
+        first: parse_bitwise_xor >>
+        rest: many0!(complete!(pair!(
+            ws!(terminated!(tag!("|"), not!(tag!("|")))),
+            parse_bitwise_xor
+        ))) >>
+        (fold_terms(first, rest))
     )
 }
 
 named! {
     parse_comparision<Ast>,
     do_parse!(
-        ident: parse_ident >>
+        lhs: parse_term >>
         op: ws!(alt_complete!(tag!("==") | tag!("!=") | tag!("<=") | tag!(">=") | tag!(">") | tag!("<"))) >>
-        val: alt_complete!(
-            map!(digit, |ds| str::from_utf8(ds).unwrap().parse::<i64>().unwrap()) |
-            parse_negative_number
-        ) >>
-        (make_comparison(ident, op, val))
+        rhs: parse_term >>
+        (make_comparison(lhs, op, rhs))
     )
 }
 
 named! {
     parse_type<Type>,
-    map!(alt_complete!(tag!("sint8") | tag!("sint16") | tag!("sint32") | tag!("sint64")), bytes_to_type)
+    map!(
+        alt_complete!(
+            tag!("sint8") | tag!("sint16") | tag!("sint32") | tag!("sint64") |
+            tag!("uint8") | tag!("uint16") | tag!("uint32") | tag!("uint64") |
+            tag!("float") | tag!("double") | tag!("bool")
+        ),
+        bytes_to_type
+    )
 }
 
 named! {
@@ -218,7 +1708,7 @@ named! {
                 ast: parse_parentheses >>
                 (vars, ast)
             ),
-            |(mut vars, ast)| { interprete(&mut vars, &ast).map(|()| Condition::Conditions(vars)) }
+            |(vars, ast)| interprete(vars, &ast)
         )
     )
 }
@@ -227,10 +1717,11 @@ named! {
 mod test {
     use nom;
     use std::collections::HashMap;
-    use std::{i32, i64};
+    use std::{f64, i32, i64, u64};
 
-    use super::{parse_comparision, parse_declaration, parse_variable_declaration, Ast, Condition,
-                Range, Type, Variable};
+    use super::super::super::float_range::{Bound, FloatRange};
+    use super::{parse_comparision, parse_declaration, parse_term, parse_variable_declaration, Ast,
+                Comparator, Condition, Domain, Range, Relation, Term, Type, Variable};
 
     #[test]
     fn test_parse_variable_declaration() {
@@ -240,14 +1731,51 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parse_array_declaration() {
+        let output = parse_declaration(
+            &b"[L]declare 'xs.length':sint32, 'xs[0]':sint32 in (('xs.length' == 1) && ('xs[0]' > 0))"[..],
+        );
+        match output {
+            nom::IResult::Done(_, Some(Condition::Conditions(ref m, ref relations))) => {
+                assert_eq!(0, relations.len());
+                assert_eq!(Domain::Integer(Range::from(1, 1)), m["xs.length"].range);
+                assert_eq!(Domain::Integer(Range::from(1, i32::MAX as i128)), m["xs[0]"].range);
+            }
+            _ => panic!("unexpected output {:?}", output),
+        }
+    }
+
     #[test]
     fn test_parse_comparison() {
         assert_eq!(
-            nom::IResult::Done(&b""[..], Ast::Lt(String::from("a"), 2)),
+            nom::IResult::Done(
+                &b""[..],
+                Ast::Compare(
+                    Term::Variable(String::from("a")),
+                    Comparator::Lt,
+                    Term::Constant(2)
+                )
+            ),
             parse_comparision(&b"'a' < 2"[..]),
         );
     }
 
+    #[test]
+    fn test_parse_variable_comparison() {
+        assert_eq!(
+            nom::IResult::Done(
+                &b""[..],
+                Ast::Compare(
+                    Term::Variable(String::from("i")),
+                    Comparator::Lte,
+                    Term::Variable(String::from("other.state"))
+                )
+            ),
+            parse_comparision(&b"'i' <= 'other.state'"[..]),
+        );
+    }
+
     #[test]
     fn test_parse_simple_declaration() {
         let mut m = HashMap::new();
@@ -256,11 +1784,12 @@ mod test {
             Variable {
                 name: String::from("a"),
                 typ: Type::SInt64,
-                range: Range::from(1, i64::MAX),
+                range: Domain::Integer(Range::from(1, i64::MAX as i128)),
+                approximate: false,
             },
         );
         let (_, output) = parse_declaration(&b"[L]declare 'a':sint64 in (('a' > 0))"[..]).unwrap();
-        assert_eq!(Some(Condition::Conditions(m)), output);
+        assert_eq!(Some(Condition::Conditions(m, Vec::new())), output);
     }
 
     #[test]
@@ -271,21 +1800,25 @@ mod test {
             Variable {
                 name: String::from("a"),
                 typ: Type::SInt32,
-                range: Range::from(i32::MIN as i64, -1),
+                range: Domain::Integer(Range::from(i32::MIN as i128, -1)),
+                approximate: false,
             },
         );
+        // b can take any value whose lowest byte is not 2, which needs too
+        // many intervals to represent
         m.insert(
             String::from("b"),
             Variable {
                 name: String::from("b"),
                 typ: Type::SInt64,
-                range: Range::from(i64::MIN, 1).union(&Range::from(3, i64::MAX)),
+                range: Domain::Integer(Type::SInt64.range()),
+                approximate: true,
             },
         );
         let (_, output) = parse_declaration(
             &b"[L]declare 'a':sint32, 'b':sint64 in (((sint64)'a' < 0) && ((sint8)'b' != 2))"[..],
         ).unwrap();
-        assert_eq!(Some(Condition::Conditions(m)), output);
+        assert_eq!(Some(Condition::Conditions(m, Vec::new())), output);
     }
 
     #[test]
@@ -296,7 +1829,8 @@ mod test {
             Variable {
                 name: String::from("a"),
                 typ: Type::SInt32,
-                range: Range::from(i32::MIN as i64, -1),
+                range: Domain::Integer(Range::from(i32::MIN as i128, -1)),
+                approximate: false,
             },
         );
         m.insert(
@@ -304,13 +1838,14 @@ mod test {
             Variable {
                 name: String::from("b"),
                 typ: Type::SInt64,
-                range: Range::from(i64::MIN, 1).union(&Range::from(3, 12)),
+                range: Domain::Integer(Type::SInt64.range()),
+                approximate: true,
             },
         );
         let (_, output) = parse_declaration(
             &b"[L]declare 'a':sint32, 'b':sint64 in (((sint64)'a' < 0) && (((sint8)'b' != 2) && ((sint8)'b' <= 12)))"[..],
         ).unwrap();
-        assert_eq!(Some(Condition::Conditions(m)), output);
+        assert_eq!(Some(Condition::Conditions(m, Vec::new())), output);
     }
 
     #[test]
@@ -321,12 +1856,516 @@ mod test {
             Variable {
                 name: String::from("n"),
                 typ: Type::SInt32,
-                range: Range::from(0, 1),
+                range: Domain::Integer(Range::from(0, 1)),
+                approximate: false,
             },
         );
         let (_, output) = parse_declaration(
             &b"[L]declare 'n':sint32 in (((sint64)'n' >= 0) && ((sint64)'n' < 2))"[..],
         ).unwrap();
-        assert_eq!(Some(Condition::Conditions(m)), output);
+        assert_eq!(Some(Condition::Conditions(m, Vec::new())), output);
+    }
+
+    #[test]
+    fn test_parse_relational_declaration() {
+        let mut m = HashMap::new();
+        m.insert(
+            String::from("i"),
+            Variable {
+                name: String::from("i"),
+                typ: Type::SInt32,
+                range: Domain::Integer(Range::from(i32::MIN as i128, 9)),
+                approximate: false,
+            },
+        );
+        m.insert(
+            String::from("other.state"),
+            Variable {
+                name: String::from("other.state"),
+                typ: Type::SInt32,
+                range: Domain::Integer(Type::SInt32.range()),
+                approximate: false,
+            },
+        );
+        let relations = vec![
+            Relation {
+                lhs: Term::Variable(String::from("i")),
+                comparator: Comparator::Lte,
+                rhs: Term::Variable(String::from("other.state")),
+                negated: false,
+            },
+        ];
+        let (_, output) = parse_declaration(
+            &b"[L]declare 'i':sint32, 'other.state':sint32 in (('i' <= 'other.state') && (10 > 'i'))"[..],
+        ).unwrap();
+        assert_eq!(Some(Condition::Conditions(m, relations)), output);
+    }
+
+    #[test]
+    fn test_parse_linear_declaration() {
+        let mut m = HashMap::new();
+        m.insert(
+            String::from("a"),
+            Variable {
+                name: String::from("a"),
+                typ: Type::SInt32,
+                range: Domain::Integer(Range::from(4, 9)),
+                approximate: false,
+            },
+        );
+        let (_, output) = parse_declaration(
+            &b"[L]declare 'a':sint32 in ((((sint64)'a' + 2) > 5) && ((3 * 'a') - 1 <= 26))"[..],
+        ).unwrap();
+        // 3 * a wraps around for large a, so the second comparison is kept
+        match output {
+            Some(Condition::Conditions(ref output, ref relations)) => {
+                let range = output["a"].range.as_integer().unwrap();
+                assert_eq!(Range::from(4, i32::MAX as i128), *range);
+                let relations: Vec<String> = relations.iter().map(|r| format!("{}", r)).collect();
+                assert_eq!(vec!["3 * a - 1 <= 26"], relations);
+            }
+            _ => panic!("unexpected condition {:?}", output),
+        }
+        let (_, output) = parse_declaration(
+            &b"[L]declare 'a':sint32 in (((('a' >= 0) && ('a' < 100)) && (((sint64)'a' + 2) > 5)) && ((3 * 'a') - 1 <= 26))"[..],
+        ).unwrap();
+        assert_eq!(Some(Condition::Conditions(m, Vec::new())), output);
+    }
+
+    #[test]
+    fn test_display_arithmetic_term() {
+        let (_, term) = parse_term(&b"(('a' + 1) * (sint64)'b') - ('c' - 2)"[..]).unwrap();
+        assert_eq!("(a + 1) * b - (c - 2)", format!("{}", term));
+        let (_, term) = parse_term(&b"-('a' % 3)"[..]).unwrap();
+        assert_eq!("-(a % 3)", format!("{}", term));
+    }
+
+    #[test]
+    fn test_parse_bitwise_declaration() {
+        let mut m = HashMap::new();
+        m.insert(
+            String::from("a"),
+            Variable {
+                name: String::from("a"),
+                typ: Type::SInt64,
+                range: Domain::Integer(Range::from(17, 19)),
+                approximate: false,
+            },
+        );
+        let (_, output) = parse_declaration(
+            &b"[L]declare 'a':sint64 in (((('a' > (1 << 4)) && ('a' < 1000)) && (('a' << 2) < 80)) && ((-1 >>> 60) == 15))"[..],
+        ).unwrap();
+        assert_eq!(Some(Condition::Conditions(m, Vec::new())), output);
+    }
+
+    #[test]
+    fn test_display_bitwise_idioms() {
+        let relation = |s: &[u8]| match parse_declaration(s).unwrap() {
+            (_, Some(Condition::Conditions(_, ref relations))) if relations.len() == 1 => {
+                format!("{}", relations[0])
+            }
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            "x is even",
+            relation(&b"[L]declare 'x':sint32 in ((('x' & 1) == 0))"[..])
+        );
+        assert_eq!(
+            "x is odd",
+            relation(&b"[L]declare 'x':sint32 in ((0 != ('x' & 1)))"[..])
+        );
+        assert_eq!(
+            "x is zero, a power of two or the minimum value of its type",
+            relation(&b"[L]declare 'x':sint64 in ((('x' & ('x' - 1)) == 0))"[..])
+        );
+        assert_eq!(
+            "(x ^ y) < 0",
+            relation(&b"[L]declare 'x':sint64, 'y':sint64 in ((('x' ^ 'y') < 0))"[..])
+        );
+        assert_eq!(
+            "(x >>> 3 | y) != 1",
+            relation(&b"[L]declare 'x':sint64, 'y':sint64 in (((('x' >>> 3) | 'y') != 1))"[..])
+        );
+    }
+
+    #[test]
+    fn test_parse_disjunctive_declaration() {
+        let mut m = HashMap::new();
+        m.insert(
+            String::from("n"),
+            Variable {
+                name: String::from("n"),
+                typ: Type::SInt32,
+                range: Domain::Integer(
+                    Range::from(i32::MIN as i128, -1)
+                        .union(&Range::from(101, 200))
+                        .union(&Range::from(300, i32::MAX as i128)),
+                ),
+                approximate: false,
+            },
+        );
+        let (_, output) = parse_declaration(
+            &b"[L]declare 'n':sint32 in ((('n' < 0) || ('n' > 100)) && !(('n' > 200) && ('n' < 300)))"[..],
+        ).unwrap();
+        assert_eq!(Some(Condition::Conditions(m, Vec::new())), output);
+    }
+
+    #[test]
+    fn test_parse_multiple_variable_disjunction() {
+        let variable = |name: &str, range: Range| {
+            (
+                String::from(name),
+                Variable {
+                    name: String::from(name),
+                    typ: Type::SInt32,
+                    range: Domain::Integer(range),
+                    approximate: false,
+                },
+            )
+        };
+        let first = vec![
+            variable("a", Range::from(i32::MIN as i128, 0)),
+            variable("b", Type::SInt32.range()),
+        ];
+        let second = vec![
+            variable("a", Type::SInt32.range()),
+            variable("b", Range::from(i32::MIN as i128, 0)),
+        ];
+        let (_, output) =
+            parse_declaration(&b"[L]declare 'a':sint32, 'b':sint32 in (!(('a' > 0) && ('b' > 0)))"[..])
+                .unwrap();
+        assert_eq!(
+            Some(Condition::Disjunction(vec![
+                Condition::Conditions(first.into_iter().collect(), Vec::new()),
+                Condition::Conditions(second.into_iter().collect(), Vec::new()),
+            ])),
+            output
+        );
+    }
+
+    #[test]
+    fn test_parse_subsumed_disjunction() {
+        let mut m = HashMap::new();
+        for name in ["a", "b"].iter() {
+            m.insert(
+                String::from(*name),
+                Variable {
+                    name: String::from(*name),
+                    typ: Type::SInt32,
+                    range: Domain::Integer(Type::SInt32.range()),
+                    approximate: false,
+                },
+            );
+        }
+        m.get_mut("a").unwrap().range = Domain::Integer(Range::from(i32::MIN as i128, 0));
+        let (_, output) = parse_declaration(
+            &b"[L]declare 'a':sint32, 'b':sint32 in (('a' <= 0) || (('a' < -5) && ('b' > 3)))"[..],
+        ).unwrap();
+        assert_eq!(Some(Condition::Conditions(m, Vec::new())), output);
+    }
+
+    #[test]
+    fn test_parse_narrowing_cast_declaration() {
+        let mut m = HashMap::new();
+        m.insert(
+            String::from("b"),
+            Variable {
+                name: String::from("b"),
+                typ: Type::SInt16,
+                range: Domain::Integer(
+                    Range::from(128, 255)
+                        .union(&Range::from(384, 511))
+                        .union(&Range::from(640, 767))
+                        .union(&Range::from(896, 999)),
+                ),
+                approximate: false,
+            },
+        );
+        let (_, output) = parse_declaration(
+            &b"[L]declare 'b':sint16 in ((((sint8)'b' < 0) && ('b' >= 0)) && ((sint32)'b' < 1000))"[..],
+        ).unwrap();
+        assert_eq!(Some(Condition::Conditions(m, Vec::new())), output);
+    }
+
+    #[test]
+    fn test_parse_wrapping_cast_declaration() {
+        let mut m = HashMap::new();
+        m.insert(
+            String::from("b"),
+            Variable {
+                name: String::from("b"),
+                typ: Type::SInt32,
+                range: Domain::Integer(
+                    Range::from(-254, -254)
+                        .union(&Range::from(2, 2))
+                        .union(&Range::from(258, 258)),
+                ),
+                approximate: false,
+            },
+        );
+        let (_, output) = parse_declaration(
+            &b"[L]declare 'b':sint32 in ((('b' > -300) && ('b' < 300)) && ((sint8)'b' == (sint8)258))"[..],
+        ).unwrap();
+        assert_eq!(Some(Condition::Conditions(m, Vec::new())), output);
+    }
+
+    #[test]
+    fn test_parse_extreme_comparisons() {
+        let range = |s: &[u8]| match parse_declaration(s).unwrap() {
+            (_, Some(Condition::Conditions(ref m, _))) => m["a"].range.as_integer().unwrap().clone(),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            Range::from(1, 0),
+            range(&b"[L]declare 'a':sint64 in (('a' < -9223372036854775808))"[..])
+        );
+        assert_eq!(
+            Range::from(1, 0),
+            range(&b"[L]declare 'a':sint64 in (('a' > 9223372036854775807))"[..])
+        );
+        assert_eq!(
+            Type::SInt64.range(),
+            range(&b"[L]declare 'a':sint64 in (('a' <= 9223372036854775807))"[..])
+        );
+        assert_eq!(
+            Range::from(i64::MIN as i128 + 1, i64::MAX as i128),
+            range(&b"[L]declare 'a':sint64 in (('a' != -9223372036854775808))"[..])
+        );
+        assert_eq!(
+            Range::from(i64::MIN as i128, i64::MAX as i128 - 1),
+            range(&b"[L]declare 'a':sint64 in ((9223372036854775807 > 'a'))"[..])
+        );
+        assert_eq!(
+            Range::from(i32::MIN as i128 + 1, i32::MAX as i128),
+            range(&b"[L]declare 'a':sint32 in (('a' > -2147483648))"[..])
+        );
+    }
+
+    #[test]
+    fn test_parse_int_arithmetic() {
+        let range = |s: &[u8]| match parse_declaration(s).unwrap() {
+            (_, Some(Condition::Conditions(ref m, _))) => m["x"].range.as_integer().unwrap().clone(),
+            _ => unreachable!(),
+        };
+        // ints only use the lowest five bits of the shift distance
+        assert_eq!(
+            Range::from(1, 1),
+            range(&b"[L]declare 'x':sint32 in ((('x' >= 0) && ('x' < 100)) && (('x' << 33) == 2))"[..])
+        );
+        // 1 << 31 is the minimum int
+        assert_eq!(
+            Range::from(i32::MIN as i128 + 1, i32::MAX as i128),
+            range(&b"[L]declare 'x':sint32 in (('x' > (1 << 31)))"[..])
+        );
+        // and the maximum int plus one wraps around to it
+        assert!(range(&b"[L]declare 'x':sint32 in (('x' < (2147483647 + 1)))"[..]).is_empty());
+        assert_eq!(
+            Range::from(4, 4),
+            range(&b"[L]declare 'x':sint64 in ((('x' >= 0) && ('x' < 100)) && (('x' << 33) == 34359738368))"[..])
+        );
+        assert_eq!(
+            Range::from(2147483649, i64::MAX as i128),
+            range(&b"[L]declare 'x':sint64 in (('x' > (2147483647 + 1)))"[..])
+        );
+    }
+
+    #[test]
+    fn test_parse_wrapping_arithmetic() {
+        let parse = |s: &[u8]| match parse_declaration(s).unwrap() {
+            (_, Some(Condition::Conditions(ref m, ref relations))) => (
+                m["a"].range.as_integer().unwrap().clone(),
+                relations.iter().map(|r| format!("{}", r)).collect::<Vec<String>>(),
+            ),
+            _ => unreachable!(),
+        };
+        let int = Range::from(i32::MIN as i128, i32::MAX as i128);
+        // the maximum int plus one wraps around to the minimum int
+        assert_eq!(
+            (int.clone(), vec![String::from("a + 1 > 0")]),
+            parse(&b"[L]declare 'a':sint32 in ((('a' + 1) > 0))"[..])
+        );
+        assert_eq!(
+            (Range::from(0, i32::MAX as i128 - 1), Vec::new()),
+            parse(&b"[L]declare 'a':sint32 in (('a' < 2147483647) && (('a' + 1) > 0))"[..])
+        );
+        // and the minimum int minus one to the maximum int
+        assert_eq!(
+            (int, vec![String::from("a - 1 < 0")]),
+            parse(&b"[L]declare 'a':sint32 in ((('a' - 1) < 0))"[..])
+        );
+        assert_eq!(
+            (Range::from(i32::MIN as i128 + 1, 0), Vec::new()),
+            parse(&b"[L]declare 'a':sint32 in (('a' > -2147483648) && (('a' - 1) < 0))"[..])
+        );
+    }
+
+    #[test]
+    fn test_parse_unsigned_declaration() {
+        let range = |s: &[u8], name: &str| match parse_declaration(s).unwrap() {
+            (_, Some(Condition::Conditions(ref m, _))) => m[name].range.as_integer().unwrap().clone(),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            Range::from(97, 122),
+            range(&b"[L]declare 'c':uint16 in (('c' >= 97) && ('c' <= 122))"[..], "c")
+        );
+        assert_eq!(
+            Range::from(32768, 65535),
+            range(&b"[L]declare 'c':uint16 in (((sint16)'c' < 0))"[..], "c")
+        );
+        assert_eq!(
+            Range::from(0, 65),
+            range(&b"[L]declare 'c':uint16 in (((sint32)'c' <= 65))"[..], "c")
+        );
+        assert_eq!(
+            Range::from(u64::MAX as i128, u64::MAX as i128),
+            range(&b"[L]declare 'x':uint64 in (('x' > 18446744073709551614))"[..], "x")
+        );
+    }
+
+    #[test]
+    fn test_char_literal() {
+        assert_eq!("'a'", Type::UInt16.literal(97));
+        assert_eq!("'\\''", Type::UInt16.literal(39));
+        assert_eq!("'\\u000a'", Type::UInt16.literal(10));
+        assert_eq!("'\\u00e9'", Type::UInt16.literal(233));
+        assert_eq!("97", Type::SInt32.literal(97));
+    }
+
+    #[test]
+    fn test_parse_float_declaration() {
+        let range = |s: &[u8]| match parse_declaration(s).unwrap() {
+            (_, Some(Condition::Conditions(ref m, _))) => m["p"].range.clone(),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            Domain::Float(FloatRange::from(Bound::open(0.0), Bound::closed(1.0))),
+            range(&b"[L]declare 'p':double in ((0.0 < 'p') && ('p' <= 1.0))"[..])
+        );
+        // NaN fails every comparison, so it satisfies the negation of one
+        assert_eq!(
+            Domain::Float(
+                FloatRange::from(Bound::closed(-0.0), Bound::closed(f64::INFINITY))
+                    .union(&FloatRange::nan())
+            ),
+            range(&b"[L]declare 'p':float in (!('p' < 0.0f))"[..])
+        );
+        assert_eq!(
+            Domain::Float(FloatRange::from(
+                Bound::closed(f64::INFINITY),
+                Bound::closed(f64::INFINITY),
+            )),
+            range(&b"[L]declare 'p':double in (('p' >= Infinity))"[..])
+        );
+        assert_eq!(
+            Domain::Float(FloatRange::nan()),
+            range(&b"[L]declare 'p':double in (('p' != NaN) && !('p' >= -Infinity))"[..])
+        );
+        assert_eq!(
+            Domain::Integer(Range::from(3, i32::MAX as i128)),
+            range(&b"[L]declare 'p':sint32 in (((double)'p' > 2.5))"[..])
+        );
+    }
+
+    #[test]
+    fn test_display_float_relation() {
+        let (_, output) = parse_declaration(
+            &b"[L]declare 'x':double, 'y':double in (!(('x' * 2.0) < -1.5E-3))"[..],
+        ).unwrap();
+        match output {
+            Some(Condition::Conditions(_, ref relations)) => {
+                assert_eq!("!(x * 2.0 < -0.0015)", format!("{}", relations[0]))
+            }
+            _ => unreachable!(),
+        }
+        assert_eq!("Double.NEGATIVE_INFINITY", Type::Float64.float_literal(f64::NEG_INFINITY));
+        assert_eq!("0.1f", Type::Float32.float_literal(0.1f32 as f64));
+        assert_eq!("Float.NaN", Type::Float32.float_literal(f64::NAN));
+    }
+
+    #[test]
+    fn test_parse_boolean_declaration() {
+        let variable = |name: &str, typ: Type, range: Range| {
+            (
+                String::from(name),
+                Variable {
+                    name: String::from(name),
+                    typ,
+                    range: Domain::Integer(range),
+                    approximate: false,
+                },
+            )
+        };
+        let first = vec![
+            variable("strict", Type::Bool, Range::from(1, 1)),
+            variable("n", Type::SInt32, Range::from(0, i32::MAX as i128)),
+        ];
+        let second = vec![
+            variable("strict", Type::Bool, Range::from(0, 0)),
+            variable("n", Type::SInt32, Type::SInt32.range()),
+        ];
+        let (_, output) = parse_declaration(
+            &b"[L]declare 'strict':bool, 'n':sint32 in ((('strict' == true) && ('n' >= 0)) || !'strict')"[..],
+        ).unwrap();
+        assert_eq!(
+            Some(Condition::Disjunction(vec![
+                Condition::Conditions(first.into_iter().collect(), Vec::new()),
+                Condition::Conditions(second.into_iter().collect(), Vec::new()),
+            ])),
+            output
+        );
+        let (_, output) =
+            parse_declaration(&b"[L]declare 'b':bool in (('b') && ('b' != false))"[..]).unwrap();
+        assert_eq!(
+            Some(Condition::Conditions(
+                vec![variable("b", Type::Bool, Range::from(1, 1))]
+                    .into_iter()
+                    .collect(),
+                Vec::new(),
+            )),
+            output
+        );
+        assert_eq!("false", Type::Bool.literal(0));
+    }
+
+    #[test]
+    fn test_parse_remainder_declaration() {
+        let range = |s: &[u8]| match parse_declaration(s).unwrap() {
+            (_, Some(Condition::Conditions(ref m, ref relations))) if relations.len() == 0 => {
+                m["n"].range.as_integer().unwrap().clone()
+            }
+            _ => unreachable!(),
+        };
+        let test = range(&b"[L]declare 'n':sint32 in ((('n' % 4) == 0) && ('n' >= 0))"[..]);
+        assert_eq!((4, 0), test.get_congruence());
+        assert_eq!(&vec![(0, i32::MAX as i128 - 3)], test.get_ranges());
+        // the remainder has the sign of the dividend
+        let test = range(&b"[L]declare 'n':sint32 in ((('n' % -3) == -1))"[..]);
+        assert_eq!((3, 2), test.get_congruence());
+        assert_eq!(&vec![(i32::MIN as i128 + 1, -1)], test.get_ranges());
+        let test = range(&b"[L]declare 'n':sint64 in (!(('n' % 2) == 0))"[..]);
+        assert_eq!((2, 1), test.get_congruence());
+        assert!(range(&b"[L]declare 'n':sint32 in (('n' % 4) == 5)"[..]).is_empty());
+    }
+
+    #[test]
+    fn test_relation_as_difference() {
+        let difference = |s: &[u8]| match parse_declaration(s).unwrap() {
+            (_, Some(Condition::Conditions(m, relations))) => relations[0].as_difference(&m),
+            _ => unreachable!(),
+        };
+        assert_eq!(
+            Some((String::from("a"), String::from("b"), Comparator::Lt, -3)),
+            difference(&b"[L]declare 'a':sint32, 'b':sint32 in (('a' < 100) && (('a' + 3) < 'b'))"[..])
+        );
+        // a + 3 wraps around for the largest a
+        assert_eq!(
+            None,
+            difference(&b"[L]declare 'a':sint32, 'b':sint32 in ((('a' + 3) < 'b'))"[..])
+        );
+        assert_eq!(
+            None,
+            difference(&b"[L]declare 'a':sint32, 'b':sint32 in (('a' >= (2 * 'b')))"[..])
+        );
     }
 }
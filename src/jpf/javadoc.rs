@@ -0,0 +1,222 @@
+use regex::Regex;
+
+/// The Javadoc comment of the first method called `method_name` in the Java
+/// source, if there is one right before its declaration.
+pub fn find_javadoc<'a>(source: &'a str, method_name: &str) -> Option<&'a str> {
+    // names may contain `$`
+    let re = Regex::new(&format!(r"\b{}\s*\(", ::regex::escape(method_name))).unwrap();
+    for m in re.find_iter(source) {
+        let before = &source[..m.start()];
+        let start = match before.rfind("/**") {
+            Some(start) => start,
+            None => continue,
+        };
+        let end = match before[start..].find("*/") {
+            Some(end) => start + end + 2,
+            None => continue,
+        };
+        // only annotations, modifiers and the return type may lie in between
+        let between = &before[end..];
+        if !between.contains(';') && !between.contains('{') && !between.contains('}') {
+            return Some(&source[start..end]);
+        }
+    }
+    None
+}
+
+/// The types of parameters the phrases on the sign of numbers apply to.
+static NUMERIC: [&str; 6] = ["byte", "short", "int", "long", "float", "double"];
+
+/// The types of parameters that cannot be null.
+static PRIMITIVES: [&str; 8] = [
+    "boolean", "byte", "char", "short", "int", "long", "float", "double",
+];
+
+/// The constraints on the parameters stated by the `@param` tags of the
+/// Javadoc, as Java conditions, e.g. `n >= 0` for
+/// `@param n a non-negative number` or `{@code n >= 0}`. The phrases on signs
+/// only apply to numeric parameters and the ones on null to objects, given
+/// the names and types of the parameters, and neither applies to hedged or
+/// conditional descriptions, e.g. `may be negative`.
+pub fn assumptions(javadoc: &str, parameters: &[(String, String)]) -> Vec<String> {
+    lazy_static! {
+        // a block tag starts a line of the comment
+        static ref TAG: Regex = Regex::new(r"(?m)^[\s*]*@(\w+)").unwrap();
+        static ref PARAM: Regex = Regex::new(r"^\s+(\w+)\s+((?s).*)$").unwrap();
+        static ref CODE: Regex = Regex::new(r"\{@code\s+([^}]*)\}").unwrap();
+        static ref COMPARISON: Regex = Regex::new(r"(<=|>=|==|!=|<|>)").unwrap();
+        static ref HEDGE: Regex = Regex::new(
+            r"\b(may|might|can|could|possibly|optionally|or|if|unless|when|otherwise)\b"
+        ).unwrap();
+        // a sign or null, possibly negated, e.g. `must not be negative`,
+        // `never negative` or `nonpositive`
+        static ref PHRASE: Regex = Regex::new(concat!(
+            r"\b(?:(?P<not>not|never|no)\s+(?:be\s+)?|(?P<non>non)-?)?",
+            r"(?P<word>negative|positive|null)\b"
+        )).unwrap();
+    }
+    let mut assumptions = Vec::new();
+    let tags: Vec<(usize, usize, bool)> = TAG.captures_iter(javadoc)
+        .map(|cap| {
+            let (start, end) = (cap.get(0).unwrap().start(), cap.get(0).unwrap().end());
+            (start, end, &cap[1] == "param")
+        })
+        .collect();
+    for (i, &(_, end, is_param)) in tags.iter().enumerate() {
+        let text_end = tags.get(i + 1).map_or(javadoc.len(), |&(start, _, _)| start);
+        let cap = match PARAM.captures(&javadoc[end..text_end]) {
+            Some(cap) if is_param => cap,
+            _ => continue,
+        };
+        let name = &cap[1];
+        // without the end of the comment and the stars starting its lines
+        let text = cap[2]
+            .replace("*/", "")
+            .lines()
+            .map(|line| line.trim_start_matches(|c: char| c.is_whitespace() || c == '*'))
+            .collect::<Vec<&str>>()
+            .join(" ");
+        let code = CODE.captures_iter(&text)
+            .map(|code| String::from(code[1].trim()))
+            .find(|code| code.contains(name) && COMPARISON.is_match(code));
+        if let Some(code) = code {
+            assumptions.push(code);
+            continue;
+        }
+        let text = text.to_lowercase();
+        let typ = match parameters.iter().find(|&&(ref param, _)| param == name) {
+            Some(&(_, ref typ)) => typ,
+            None => continue,
+        };
+        if HEDGE.is_match(&text) {
+            continue;
+        }
+        let phrase = match PHRASE.captures(&text) {
+            Some(cap) => cap,
+            None => continue,
+        };
+        let negated = phrase.name("not").is_some() || phrase.name("non").is_some();
+        // the condition, and whether it applies to numbers rather than objects
+        let condition = match (&phrase["word"], negated) {
+            ("negative", false) => Some(("< 0", true)),
+            ("negative", true) => Some((">= 0", true)),
+            ("positive", false) => Some(("> 0", true)),
+            ("positive", true) => Some(("<= 0", true)),
+            // a mention of null alone does not require it
+            ("null", true) => Some(("!= null", false)),
+            _ => None,
+        };
+        match condition {
+            Some((condition, true)) if NUMERIC.contains(&typ.as_str()) => {
+                assumptions.push(format!("{} {}", name, condition));
+            }
+            Some((condition, false)) if !PRIMITIVES.contains(&typ.as_str()) => {
+                assumptions.push(format!("{} {}", name, condition));
+            }
+            _ => (),
+        }
+    }
+    assumptions
+}
+
+#[cfg(test)]
+mod test {
+    use super::{assumptions, find_javadoc};
+
+    #[test]
+    fn test_find_javadoc() {
+        let source = "
+  /** Adds. */
+  int add(int a, int b) { return a + b; }
+
+  /**
+   * Checks.
+   * @param n a non-negative number
+   */
+  @Override
+  public static boolean isPrime(long n) {
+    return add(1, 2) > 0;
+  }
+";
+        assert_eq!(Some("/** Adds. */"), find_javadoc(source, "add"));
+        let javadoc = find_javadoc(source, "isPrime").unwrap();
+        assert!(javadoc.starts_with("/**\n   * Checks."));
+        assert_eq!(None, find_javadoc(source, "sub"));
+        let source = "
+  /** Gets. */
+  int get$0() { return 0; }
+";
+        assert_eq!(Some("/** Gets. */"), find_javadoc(source, "get$0"));
+    }
+
+    fn parameters(params: &[(&str, &str)]) -> Vec<(String, String)> {
+        params
+            .iter()
+            .map(|&(name, typ)| (String::from(name), String::from(typ)))
+            .collect()
+    }
+
+    #[test]
+    fn test_assumptions() {
+        let javadoc = "/**
+   * Returns the element.
+   * @param xs the elements, must not be null
+   * @param i the index, {@code i >= 0 && i < xs.length}
+   * @param n a positive number
+   * @param s any string
+   */";
+        let params = parameters(&[("xs", "int[]"), ("i", "int"), ("n", "long"), ("s", "String")]);
+        assert_eq!(
+            vec!["xs != null", "i >= 0 && i < xs.length", "n > 0"],
+            assumptions(javadoc, &params)
+        );
+    }
+
+    #[test]
+    fn test_assumptions_of_hedged_or_mistyped_phrases() {
+        let javadoc = "/**
+   * Adds the offset.
+   * @param offset the offset, may be negative
+   * @param flag whether the result is positive
+   * @param n a non-null count
+   * @param step the step, positive unless {@code flag} is set
+   * @param delta the change, {@code delta != 0}
+   * @param other a number not mentioned in the signature, positive
+   */";
+        let params = parameters(&[
+            ("offset", "int"),
+            ("flag", "boolean"),
+            ("n", "int"),
+            ("step", "int"),
+            ("delta", "double"),
+        ]);
+        assert_eq!(vec!["delta != 0"], assumptions(javadoc, &params));
+    }
+
+    #[test]
+    fn test_assumptions_of_negated_phrases() {
+        let javadoc = "/**
+   * Counts.
+   * @param a the count, must not be negative
+   * @param b never negative
+   * @param c a nonpositive number
+   * @param d a non-negative number
+   * @param e not positive
+   * @param s the name, never null
+   * @param t the name, null for none
+   */";
+        let params = parameters(&[
+            ("a", "int"),
+            ("b", "int"),
+            ("c", "long"),
+            ("d", "double"),
+            ("e", "int"),
+            ("s", "String"),
+            ("t", "String"),
+        ]);
+        assert_eq!(
+            vec!["a >= 0", "b >= 0", "c <= 0", "d >= 0", "e <= 0", "s != null"],
+            assumptions(javadoc, &params)
+        );
+    }
+}
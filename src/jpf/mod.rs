@@ -1,32 +1,55 @@
 use mustache::{self, MapBuilder};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
+use std::f64;
 use std::fmt::{self, Write};
 use std::fs::File;
+use std::fs;
 use std::path::PathBuf;
 use std::process::{self, Command};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use self::expression::Condition;
+use self::expression::{Comparator, Condition, Conjunct, Domain};
+use super::dbm::Dbm;
+use super::float_range::FloatRange;
+use super::range::Range;
 use super::{construct_path, json, Config};
 
+pub mod driver;
 pub mod expression;
+pub mod javadoc;
+mod quantifier;
+pub mod strings;
+
+/// Upper bound on the number of example calls shown for one outcome.
+const MAX_EXAMPLES: usize = 2;
 
 static SPF_TEMPLATE: &str = r"
 shell=gov.nasa.jpf.jdart.summaries.MethodSummarizer
 report.console.start=
 report.console.finished=
 report.console.property_violation=
-symbolic.dp=z3
-symbolic.dp.z3.bitvectors=true
+symbolic.dp={{solver}}
+symbolic.dp.{{solver}}.bitvectors={{bitvectors}}
 target={{package}}.{{class}}
 classpath={{classpath}}
 jdart.summarystore={{output_path}}
 summary.methods={{method_name}}
 concolic.method.{{method_name}}={{method_signature}}
 concolic.method.{{method_name}}.config={{method_name}}
-jdart.configs.{{method_name}}.symbolic.statics={{package}}.{{class}}
-jdart.configs.{{method_name}}.symbolic.include=this.*;{{package}}.{{class}}.*
+jdart.configs.{{method_name}}.symbolic.statics={{statics}}
+jdart.configs.{{method_name}}.symbolic.include={{include}}
+{{#has_constraints}}
+concolic.method.{{method_name}}.constraints={{{constraints}}}
+{{/has_constraints}}
+{{#has_exclude}}
+jdart.configs.{{method_name}}.symbolic.exclude={{exclude}}
+{{/has_exclude}}
 jdart.configs.{{method_name}}.max_depth={{max_depth}}
+{{#properties}}
+{{key}}={{{value}}}
+{{/properties}}
 ";
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -34,6 +57,62 @@ struct MethodSummary {
     summaries: HashMap<String, json::Value>,
 }
 
+/// A path of the method that ends in an exception.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorPath {
+    path_condition: String,
+    /// the fully qualified name of the exception
+    #[serde(default = "unknown_exception")]
+    exception_class: String,
+}
+
+fn unknown_exception() -> String {
+    String::from("Exception")
+}
+
+impl ErrorPath {
+    /// The name of the exception without its package, as used in `@throws`.
+    fn exception_name(&self) -> &str {
+        self.exception_class.rsplit('.').next().unwrap()
+    }
+}
+
+/// The outcome of running JPF with increasing maximum depths.
+#[derive(Debug)]
+pub struct Deepening {
+    pub out_json_path: String,
+    /// the smallest depth explored that gives the final results
+    pub depth: u32,
+    /// whether the results stopped changing before the time budget ran out
+    pub converged: bool,
+}
+
+#[derive(Debug)]
+struct JpfFailed {
+    description: String,
+}
+
+impl JpfFailed {
+    fn at_depth(depth: u32) -> JpfFailed {
+        JpfFailed {
+            description: format!("JPF exited with an error at depth {}", depth),
+        }
+    }
+}
+
+impl fmt::Display for JpfFailed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", &self.description)
+    }
+}
+
+impl Error for JpfFailed {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
 #[derive(Debug)]
 struct NoValidValue {
     description: String,
@@ -60,149 +139,1196 @@ impl Error for NoValidValue {
 }
 
 fn ranges_to_string(
-    ranges: &[(i64, i64)],
+    range: &Range,
     name: &str,
-    lower: i64,
-    upper: i64,
+    typ: &expression::Type,
 ) -> Result<String, Box<Error>> {
+    let ranges = range.get_ranges();
     if ranges.len() == 0 {
         return Err(Box::new(NoValidValue::for_variable(name)));
     }
-    if ranges.len() == 1 && ranges[0] == (lower, upper) {
-        return Ok(String::new());
+    let (modulus, remainder) = range.get_congruence();
+    // the smallest and largest values of the type satisfying the congruence
+    let (lower, upper) = {
+        let bounds = typ.range().intersect(&Range::congruent(modulus, remainder));
+        let bounds = bounds.get_ranges();
+        (bounds[0].0, bounds[bounds.len() - 1].1)
+    };
+    let literal = |val: i128| typ.literal(val);
+    let mut conditions = Vec::new();
+    // the bounds of half-lines in a disjunction face a gap, which reads better
+    // as a strict comparison, e.g. `n < 0 || n > 100`
+    let strict = modulus == 1 && ranges.len() > 1;
+    if strict && ranges.len() == 2 && ranges[0].0 == lower && ranges[1].1 == upper
+        && ranges[1].0 - ranges[0].1 == 2
+    {
+        conditions.push(format!("{} != {}", name, literal(ranges[0].1 + 1)));
+    } else if ranges.len() != 1 || ranges[0] != (lower, upper) {
+        for &(l, u) in ranges.iter() {
+            conditions.push(if l == u {
+                format!("{} == {}", name, literal(l))
+            } else if l == lower && strict {
+                format!("{} < {}", name, literal(u + 1))
+            } else if l == lower {
+                format!("{} <= {}", name, literal(u))
+            } else if u == upper && strict {
+                format!("{} > {}", name, literal(l - 1))
+            } else if u == upper {
+                format!("{} >= {}", name, literal(l))
+            } else {
+                format!("{} >= {} && {} <= {}", name, literal(l), name, literal(u))
+            });
+        }
     }
-    let mut s = String::new();
+    // only conjunctions in a disjunction need parentheses
+    let disjunction = conditions
+        .iter()
+        .map(|c| if conditions.len() > 1 && c.contains(" && ") {
+            format!("({})", c)
+        } else {
+            c.clone()
+        })
+        .collect::<Vec<String>>()
+        .join(" || ");
+    if modulus == 1 {
+        return Ok(disjunction);
+    }
+    // the result of % has the sign of the dividend in Java
+    let congruence = if remainder == 0 {
+        format!("{} % {} == 0", name, modulus)
+    } else if modulus == 2 {
+        format!("{} % 2 != 0", name)
+    } else if ranges[0].0 >= 0 {
+        format!("{} % {} == {}", name, modulus, remainder)
+    } else if ranges[ranges.len() - 1].1 < 0 {
+        format!("{} % {} == {}", name, modulus, remainder - modulus)
+    } else {
+        format!("Math.floorMod({}, {}) == {}", name, modulus, remainder)
+    };
+    Ok(match conditions.len() {
+        0 => congruence,
+        1 => format!("{} && {}", congruence, disjunction),
+        _ => format!("{} && ({})", congruence, disjunction),
+    })
+}
+
+fn float_range_to_string(
+    range: &FloatRange,
+    name: &str,
+    typ: &expression::Type,
+) -> Result<String, Box<Error>> {
+    if range.is_empty() {
+        return Err(Box::new(NoValidValue::for_variable(name)));
+    }
+    let class = if *typ == expression::Type::Float32 {
+        "Float"
+    } else {
+        "Double"
+    };
+    let intervals = range.get_intervals();
     let mut conditions = Vec::new();
-    for &(l, u) in ranges.iter() {
-        s.clear();
-        s.push('(');
-        if l == u {
-            write!(&mut s, "{} == {})", name, l)?;
+    for &(l, u) in intervals.iter() {
+        let unbounded_below = l.closed && l.value == f64::NEG_INFINITY;
+        let unbounded_above = u.closed && u.value == f64::INFINITY;
+        if unbounded_below && unbounded_above {
+            if range.has_nan() {
+                return Ok(String::new());
+            }
+            conditions.push(format!("!{}.isNaN({})", class, name));
+            continue;
+        }
+        let mut s = String::new();
+        if l.value == u.value {
+            write!(&mut s, "{} == {}", name, typ.float_literal(l.value))?;
         } else {
-            if l > lower {
-                write!(&mut s, "{} >= {}", name, l)?;
-                if u < upper {
+            if !unbounded_below {
+                let op = if l.closed { ">=" } else { ">" };
+                write!(&mut s, "{} {} {}", name, op, typ.float_literal(l.value))?;
+                if !unbounded_above {
                     s.push_str(" && ");
-                } else {
-                    s.push(')');
                 }
             }
-            if u < upper {
-                write!(&mut s, "{} <= {})", name, u)?;
+            if !unbounded_above {
+                let op = if u.closed { "<=" } else { "<" };
+                write!(&mut s, "{} {} {}", name, op, typ.float_literal(u.value))?;
             }
         }
-        conditions.push(s.clone());
+        conditions.push(s);
+    }
+    if range.has_nan() {
+        conditions.push(format!("{}.isNaN({})", class, name));
     }
-    Ok(format!("{}", conditions.join(" || ")))
+    // only conjunctions in a disjunction need parentheses
+    Ok(conditions
+        .iter()
+        .map(|c| if conditions.len() > 1 && c.contains(" && ") {
+            format!("({})", c)
+        } else {
+            c.clone()
+        })
+        .collect::<Vec<String>>()
+        .join(" || "))
 }
 
-fn variable_conditions_to_string(
+fn domain_to_string(
+    domain: &Domain,
+    name: &str,
+    typ: &expression::Type,
+) -> Result<String, Box<Error>> {
+    if let Some(c) = strings::domain_to_string(domain, name, typ) {
+        return c;
+    }
+    match *domain {
+        Domain::Integer(ref range) => ranges_to_string(range, name, typ),
+        Domain::Float(ref range) => float_range_to_string(range, name, typ),
+    }
+}
+
+fn conditions_to_string(
     m: &HashMap<String, expression::Variable>,
+    relations: &[expression::Relation],
 ) -> Result<String, Box<Error>> {
-    let mut s = String::new();
-    for (_, var) in m.iter() {
-        let (l, u) = {
-            let range = var.typ.range();
-            range.get_ranges()[0]
-        };
-        let c = ranges_to_string(var.range.get_ranges(), &var.name, l, u)?;
+    let mut conditions = Vec::new();
+    let mut vars: Vec<&expression::Variable> = m.values().collect();
+    vars.sort_by(|a, b| a.name.cmp(&b.name));
+    for var in vars.into_iter() {
+        let c = domain_to_string(&var.range, &var.name, &var.typ)?;
         if c.len() != 0 {
-            s.push_str(&c);
+            let disjunctive = c.contains(" || ");
+            conditions.push((c, disjunctive));
+        }
+    }
+    for relation in relations.iter() {
+        conditions.push((format!("{}", relation), false));
+    }
+    let parenthesize = conditions.len() > 1;
+    Ok(conditions
+        .into_iter()
+        .map(|(c, disjunctive)| {
+            if parenthesize && disjunctive {
+                format!("({})", c)
+            } else {
+                c
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" && "))
+}
+
+/// The path condition as a difference-bound matrix, if every variable is an
+/// integer bounded by a single interval and every relation a difference of two
+/// variables.
+fn path_to_dbm(
+    m: &HashMap<String, expression::Variable>,
+    relations: &[expression::Relation],
+) -> Option<Dbm> {
+    let mut names: Vec<String> = m.keys().cloned().collect();
+    names.sort();
+    let mut dbm = Dbm::new(names);
+    for var in m.values() {
+        // chars and booleans read better as literals than as numbers
+        if var.typ == expression::Type::UInt16 || var.typ == expression::Type::Bool {
+            return None;
+        }
+        let range = var.range.as_integer()?;
+        if range.get_congruence().0 != 1 || range.get_ranges().len() != 1 {
+            return None;
+        }
+        let (l, u) = range.get_ranges()[0];
+        let (lower, upper) = var.typ.range().get_ranges()[0];
+        if l > lower {
+            dbm.constrain(None, Some(&var.name), -l);
+        }
+        if u < upper {
+            dbm.constrain(Some(&var.name), None, u);
+        }
+    }
+    for relation in relations.iter() {
+        let (x, y, comparator, c) = relation.as_difference(m)?;
+        dbm.orient(&x, &y);
+        let (x, y) = (Some(&x[..]), Some(&y[..]));
+        match comparator {
+            Comparator::Lt => dbm.constrain(x, y, c - 1),
+            Comparator::Lte => dbm.constrain(x, y, c),
+            Comparator::Gt => dbm.constrain(y, x, -c - 1),
+            Comparator::Gte => dbm.constrain(y, x, -c),
+            Comparator::Eq => {
+                dbm.constrain(x, y, c);
+                dbm.constrain(y, x, -c);
+            }
+            Comparator::Neq => return None,
         }
-        s.push_str(" && ");
     }
-    if s.len() >= 4 {
-        let len = s.len() - 4;
-        s.truncate(len);
+    Some(dbm)
+}
+
+/// A variable fixed by a path condition, by which the paths are grouped.
+type FixedValue = (String, i128);
+
+/// The value of `var` if it is a boolean or the length of an array fixed by
+/// the path condition.
+fn fixed_value(var: &expression::Variable) -> Option<FixedValue> {
+    if var.typ != expression::Type::Bool && !var.name.ends_with(".length") {
+        return None;
+    }
+    match var.range.as_integer().map(|range| range.get_ranges()) {
+        Some(ranges) if ranges.len() == 1 && ranges[0].0 == ranges[0].1 => {
+            Some((var.name.clone(), ranges[0].0))
+        }
+        _ => None,
     }
-    Ok(s)
 }
 
-pub fn process_output(out_json_path: &str) -> Result<String, Box<Error>> {
+/// Groups the paths by the values they fix, see `fixed_value`, and removes
+/// the fixed variables from the paths.
+fn group_by_fixed_values(conditions: Vec<Condition>) -> BTreeMap<Vec<FixedValue>, Vec<Condition>> {
+    let mut groups: BTreeMap<Vec<FixedValue>, Vec<Condition>> = BTreeMap::new();
+    for condition in conditions.into_iter() {
+        match condition {
+            Condition::Conditions(mut m, relations) => {
+                let mut fixed: Vec<FixedValue> = m.values().filter_map(fixed_value).collect();
+                fixed.sort();
+                for &(ref name, _) in fixed.iter() {
+                    m.remove(name);
+                }
+                groups
+                    .entry(fixed)
+                    .or_insert_with(Vec::new)
+                    .push(Condition::Conditions(m, relations));
+            }
+            c => groups.entry(Vec::new()).or_insert_with(Vec::new).push(c),
+        }
+    }
+    groups
+}
+
+/// The conditions stating the values fixed for a group of paths, e.g.
+/// `strict == true` or `xs.length == 3`.
+fn fixed_value_conditions(fixed: &[FixedValue]) -> Vec<String> {
+    fixed
+        .iter()
+        .map(|&(ref name, val)| {
+            let (domain, typ) = if name.ends_with(".length") {
+                (Domain::Integer(Range::from(val, val)), expression::Type::SInt32)
+            } else {
+                (Domain::Integer(Range::from(val, val)), expression::Type::Bool)
+            };
+            match strings::domain_to_string(&domain, name, &typ) {
+                Some(Ok(c)) => c,
+                _ => format!("{} == {}", name, typ.literal(val)),
+            }
+        })
+        .collect()
+}
+
+/// Describes the values fixed for a group of paths, e.g. `when strict == true`
+/// or `for xs.length == 3`.
+fn describe_fixed_values(fixed: &[FixedValue]) -> String {
+    let lengths = fixed.iter().all(|&(ref name, _)| name.ends_with(".length"));
+    format!(
+        "{} {}",
+        if lengths { "for" } else { "when" },
+        fixed_value_conditions(fixed).join(" and ")
+    )
+}
+
+fn read_summary(out_json_path: &str) -> Result<MethodSummary, Box<Error>> {
     let mut file = File::open(out_json_path)?;
-    let method_summary: MethodSummary = json::from_reader(&mut file)?;
-    let mut unparsable = Vec::new();
-    let mut parsable_with_one_variable = HashMap::new();
-    let mut parsable_with_multiple_variables = Vec::new();
-    let mut has_error_paths = false;
-    for (_method_name, summary) in method_summary.summaries.iter() {
-        if let json::Value::Array(ref v) = summary["errorPaths"] {
-            has_error_paths = v.len() > 0;
-        }
-        match summary["okPaths"] {
-            json::Value::Array(ref v) => for ok_path in v.iter() {
-                match ok_path["pathCondition"] {
-                    json::Value::String(ref s) => match expression::Expression::from_str(s) {
-                        expression::Expression::Unparsable(s) => unparsable.push(s),
-                        expression::Expression::Parsed(Condition::True) => {
-                            return Ok(String::from("None"))
-                        }
-                        expression::Expression::Parsed(Condition::Conditions(mut m)) => {
-                            if m.len() == 1 {
-                                match m.drain().take(1).next() {
-                                    Some((name, var)) => {
-                                        if !parsable_with_one_variable.contains_key(&name) {
-                                            parsable_with_one_variable.insert(name, var);
-                                        } else {
-                                            let range = parsable_with_one_variable[&name]
-                                                .range
-                                                .union(&var.range);
-                                            parsable_with_one_variable
-                                                .get_mut(&name)
-                                                .unwrap()
-                                                .range = range;
-                                        }
-                                    }
-                                    _ => unreachable!(),
-                                }
-                            } else {
-                                parsable_with_multiple_variables.push(m);
-                            }
+    Ok(json::from_reader(&mut file)?)
+}
+
+/// The paths of the given kind, e.g. `okPaths`, where a missing list stands
+/// for no paths.
+fn paths<'a>(summary: &'a json::Value, kind: &str) -> &'a [json::Value] {
+    match summary[kind] {
+        json::Value::Array(ref v) => v,
+        _ => &[],
+    }
+}
+
+/// Parses the path conditions of the paths of the given kind, e.g. `okPaths`,
+/// into `conditions`, and keeps the ones that cannot be parsed in `unparsable`.
+fn parse_paths(
+    summary: &json::Value,
+    kind: &str,
+    conditions: &mut Vec<Condition>,
+    unparsable: &mut Vec<String>,
+) {
+    for path in paths(summary, kind).iter() {
+        parse_path(path, conditions, unparsable);
+    }
+}
+
+fn parse_path(path: &json::Value, conditions: &mut Vec<Condition>, unparsable: &mut Vec<String>) {
+    match path["pathCondition"] {
+        json::Value::String(ref s) => match expression::Expression::from_str(s) {
+            expression::Expression::Unparsable(s) => unparsable.push(s),
+            expression::Expression::Parsed(Condition::Disjunction(v)) => conditions.extend(v),
+            expression::Expression::Parsed(c) => conditions.push(c),
+        },
+        _ => unreachable!(),
+    }
+}
+
+fn parse_error_paths(summary: &json::Value) -> Result<Vec<ErrorPath>, Box<Error>> {
+    Ok(paths(summary, "errorPaths")
+        .iter()
+        .map(|path| json::from_value(path.clone()))
+        .collect::<Result<Vec<ErrorPath>, json::Error>>()?)
+}
+
+/// Describes when the method throws each of its exceptions, e.g.
+/// `throws RuntimeException when a <= -1`, from the conditions of the error
+/// paths grouped by exception.
+pub fn throws(out_json_path: &str) -> Result<BTreeMap<String, Vec<String>>, Box<Error>> {
+    per_method(out_json_path, method_throws)
+}
+
+fn method_throws(summary: &json::Value) -> Result<Vec<String>, Box<Error>> {
+    let mut groups: BTreeMap<String, (Vec<Condition>, Vec<String>)> = BTreeMap::new();
+    for path in parse_error_paths(summary)?.into_iter() {
+        let &mut (ref mut conditions, ref mut unparsable) = groups
+            .entry(String::from(path.exception_name()))
+            .or_insert_with(|| (Vec::new(), Vec::new()));
+        match expression::Expression::from_str(&path.path_condition) {
+            expression::Expression::Unparsable(s) => unparsable.push(s),
+            expression::Expression::Parsed(Condition::Disjunction(v)) => conditions.extend(v),
+            expression::Expression::Parsed(c) => conditions.push(c),
+        }
+    }
+    let mut throws = Vec::new();
+    for (exception, (conditions, unparsable)) in groups.into_iter() {
+        let mut unparsable = Some(unparsable);
+        for (fixed, conditions) in group_by_fixed_values(conditions).into_iter() {
+            let prefix = if fixed.len() == 0 {
+                String::new()
+            } else {
+                format!("{}, ", describe_fixed_values(&fixed))
+            };
+            let unparsable = if fixed.len() == 0 {
+                unparsable.take().unwrap()
+            } else {
+                Vec::new()
+            };
+            match combine_paths(conditions, &fixed, unparsable, false)?.as_str() {
+                "None" => throws.push(format!("{}throws {}", prefix, exception)),
+                // the exception cannot actually be thrown
+                "No satisfiable value" => (),
+                c => throws.push(format!("{}throws {} when {}", prefix, exception, c)),
+            }
+        }
+        if let Some(unparsable) = unparsable {
+            if unparsable.len() != 0 {
+                let c = combine_paths(Vec::new(), &[], unparsable, false)?;
+                throws.push(format!("throws {} when {}", exception, c));
+            }
+        }
+    }
+    Ok(throws)
+}
+
+/// The value returned on an ok path as Java, or None for void methods.
+fn result_to_string(result: &json::Value) -> Option<String> {
+    match *result {
+        json::Value::Null => None,
+        // symbolic results refer to the parameters as 'name'
+        json::Value::String(ref s) => Some(s.replace('\'', "")),
+        ref v => Some(v.to_string()),
+    }
+}
+
+/// Describes the value returned by the method, e.g.
+/// `returns 0 when i <= 0, returns 1 otherwise`, from the conditions of the
+/// ok paths grouped by result. Empty for void methods.
+pub fn returns(out_json_path: &str) -> Result<BTreeMap<String, String>, Box<Error>> {
+    per_method(out_json_path, method_returns)
+}
+
+fn method_returns(summary: &json::Value) -> Result<String, Box<Error>> {
+    let mut groups: BTreeMap<String, (Vec<Condition>, Vec<String>)> = BTreeMap::new();
+    let has_error_paths = has_error_paths(summary);
+    for path in paths(summary, "okPaths").iter() {
+        let result = match result_to_string(&path["result"]) {
+            Some(result) => result,
+            None => continue,
+        };
+        let &mut (ref mut conditions, ref mut unparsable) =
+            groups.entry(result).or_insert_with(|| (Vec::new(), Vec::new()));
+        parse_path(path, conditions, unparsable);
+    }
+    // the ok paths cover every input if no path ends in an error or was left
+    // unexplored, and every path condition is known
+    let exact = !has_error_paths && count_paths(summary, "skippedPaths") == 0
+        && count_paths(summary, "dontKnowPaths") == 0
+        && groups
+            .values()
+            .all(|&(_, ref unparsable)| unparsable.len() == 0);
+    // the results with their conditions and the number of paths reaching them
+    let mut results = Vec::new();
+    for (result, (conditions, unparsable)) in groups.into_iter() {
+        let count = conditions.len() + unparsable.len();
+        match combine_paths(conditions, &[], unparsable, false)? {
+            ref c if c == "No satisfiable value" => (),
+            c => results.push((result, c, count)),
+        }
+    }
+    // the result of all other inputs is the one reached on the most paths,
+    // whose condition takes the most disjuncts to state
+    let otherwise = if exact && results.len() > 1 {
+        (0..results.len()).max_by_key(|&i| (results[i].2, results[i].1.len()))
+    } else {
+        None
+    };
+    let mut returns = Vec::new();
+    for (i, &(ref result, ref c, _)) in results.iter().enumerate() {
+        if Some(i) == otherwise {
+            continue;
+        }
+        returns.push(if c == "None" {
+            format!("returns {}", result)
+        } else {
+            format!("returns {} when {}", result, c)
+        });
+    }
+    if let Some(i) = otherwise {
+        returns.push(format!("returns {} otherwise", results[i].0));
+    }
+    Ok(returns.join(", "))
+}
+
+/// The names in the declaration of a path condition, in the order of the
+/// declaration, e.g. `a` and `b` for `[L]declare 'a':sint32, 'b':sint32 in ..`.
+fn declared_names(path_condition: &str) -> Vec<String> {
+    let start = path_condition.find("declare ").map_or(0, |i| i + "declare ".len());
+    let end = path_condition.find(" in ").unwrap_or(path_condition.len());
+    path_condition[start..end]
+        .split(", ")
+        .filter_map(|decl| decl.rsplitn(2, ':').nth(1))
+        .map(|name| name.trim_matches('\'').to_string())
+        .collect()
+}
+
+/// The value JDart used for a variable on a path as Java.
+fn value_to_string(value: &json::Value, var: Option<&expression::Variable>) -> String {
+    match (value, var.map(|var| &var.typ)) {
+        (&json::Value::Number(ref n), Some(typ)) if typ.is_float() => {
+            typ.float_literal(n.as_f64().unwrap())
+        }
+        (&json::Value::Number(ref n), Some(typ)) => match n.as_i64() {
+            Some(val) => typ.literal(val as i128),
+            None => n.to_string(),
+        },
+        (&json::Value::String(ref s), _) => s.clone(),
+        (v, _) => v.to_string(),
+    }
+}
+
+/// The values JDart used for the variables of a path, which are recorded
+/// either as an object or as `name:=value` pairs.
+fn valuation(path: &json::Value) -> HashMap<String, json::Value> {
+    match path["valuation"] {
+        json::Value::Object(ref m) => m.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+        json::Value::String(ref s) => s.split(',')
+            .filter_map(|pair| {
+                let mut it = pair.splitn(2, ":=");
+                let name = it.next()?.trim().trim_matches('\'');
+                let value = it.next()?.trim();
+                let value = json::from_str(value)
+                    .unwrap_or_else(|_| json::Value::String(String::from(value)));
+                Some((String::from(name), value))
+            })
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
+
+/// A value of every variable of the path, taken from the bounds of its range,
+/// unless a relation could be violated by them.
+fn boundary_valuation(
+    m: &HashMap<String, expression::Variable>,
+    relations: &[expression::Relation],
+) -> Option<HashMap<String, String>> {
+    if relations.len() != 0 {
+        return None;
+    }
+    m.iter()
+        .map(|(name, var)| {
+            let value = match var.range {
+                Domain::Integer(ref range) => var.typ.literal(range.example()?),
+                Domain::Float(ref range) => var.typ.float_literal(range.example()?),
+            };
+            Some((name.clone(), value))
+        })
+        .collect()
+}
+
+/// The value Java initializes elements of the type with.
+fn default_value(typ: &str) -> &'static str {
+    match typ {
+        "boolean" => "false",
+        "char" => "'\\0'",
+        "byte" | "short" | "int" | "long" => "0",
+        "float" | "double" => "0.0",
+        _ => "null",
+    }
+}
+
+/// Formats the array parameter `name` with elements of type `typ` from the
+/// values of its length and elements, e.g. `new int[]{1, 1}`, where the
+/// elements without a value take the default one.
+fn array_value(name: &str, typ: &str, values: &HashMap<String, String>) -> String {
+    let element = |i: usize| values.get(&format!("{}[{}]", name, i));
+    let length = match values.get(&format!("{}.length", name)) {
+        Some(length) => length.parse::<usize>().ok(),
+        // the elements the path refers to
+        None => (0..).find(|&i| element(i).is_none()).filter(|&length| length != 0),
+    };
+    match length {
+        Some(length) => format!(
+            "new {}[]{{{}}}",
+            typ,
+            (0..length)
+                .map(|i| element(i).map_or(default_value(typ), |v| v.as_str()))
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ),
+        None => String::from("?"),
+    }
+}
+
+/// Formats a call of `method`, given by its signature as JDart expects it,
+/// with the values of the variables, e.g. `count(new int[]{1, 1}, 3)`, with
+/// the fields of objects, e.g. `other.state`, given after the call.
+fn example_call(method: &str, names: &[String], values: &HashMap<String, String>) -> String {
+    // the method name without its class and signature
+    let name = method.split('(').next().unwrap().rsplit('.').next().unwrap();
+    let parameters = signature_parameters(method);
+    let value = |name: &str| values.get(name).map_or("?", |v| v.as_str());
+    let mut call = format!(
+        "{}({})",
+        name,
+        parameters
+            .iter()
+            .map(|&(ref param, ref typ)| if typ.ends_with("[]") {
+                array_value(param, &typ[..typ.len() - 2], values)
+            } else {
+                String::from(value(param))
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+    // the lengths and elements of arrays are part of the arguments
+    let is_array = |name: &str| {
+        parameters.iter().any(|&(ref param, ref typ)| {
+            typ.ends_with("[]") && name.starts_with(param.as_str())
+                && (name[param.len()..].starts_with('[') || &name[param.len()..] == ".length")
+        })
+    };
+    let fields: Vec<String> = names
+        .iter()
+        .filter(|name| name.contains('.') && !is_array(name))
+        .map(|name| format!("{} == {}", name, value(name)))
+        .collect();
+    if fields.len() != 0 {
+        call.push_str(&format!(" with {}", fields.join(" && ")));
+    }
+    call
+}
+
+/// Illustrative calls of the method for each of its outcomes, e.g.
+/// `isPrime(4) returns false`, chosen from the values JDart used on the paths,
+/// or from the bounds of their path conditions.
+fn method_examples(method: &str, summary: &json::Value) -> Vec<String> {
+    let mut outcomes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for kind in ["okPaths", "errorPaths"].iter() {
+        for path in paths(summary, kind).iter() {
+            let outcome = if *kind == "okPaths" {
+                match result_to_string(&path["result"]) {
+                    Some(result) => format!("returns {}", result),
+                    None => String::from("returns normally"),
+                }
+            } else {
+                match json::from_value::<ErrorPath>(path.clone()) {
+                    Ok(error) => format!("throws {}", error.exception_name()),
+                    Err(_) => continue,
+                }
+            };
+            let condition = match path["pathCondition"] {
+                json::Value::String(ref s) => s,
+                _ => continue,
+            };
+            let mut conditions = Vec::new();
+            parse_path(path, &mut conditions, &mut Vec::new());
+            let m = match conditions.first() {
+                Some(&Condition::Conditions(ref m, _)) => Some(m),
+                _ => None,
+            };
+            let valuation = valuation(path);
+            let values = if valuation.len() != 0 {
+                valuation
+                    .iter()
+                    .map(|(name, v)| {
+                        (name.clone(), value_to_string(v, m.and_then(|m| m.get(name))))
+                    })
+                    .collect()
+            } else {
+                match conditions.first() {
+                    Some(&Condition::Conditions(ref m, ref relations)) => {
+                        match boundary_valuation(m, relations) {
+                            Some(values) => values,
+                            None => continue,
                         }
-                    },
-                    _ => unreachable!(),
+                    }
+                    Some(&Condition::True) => HashMap::new(),
+                    _ => continue,
                 }
-            },
-            _ => unreachable!(),
+            };
+            let call = example_call(method, &declared_names(condition), &values);
+            let examples = outcomes.entry(outcome).or_insert_with(Vec::new);
+            if examples.len() < MAX_EXAMPLES && !examples.contains(&call) {
+                examples.push(call);
+            }
         }
     }
-    if unparsable.len() == 0 && parsable_with_one_variable.len() == 0
-        && parsable_with_multiple_variables.len() == 0
-    {
+    outcomes
+        .into_iter()
+        .flat_map(|(outcome, calls)| {
+            calls
+                .into_iter()
+                .map(move |call| format!("{} {}", call, outcome))
+        })
+        .collect()
+}
+
+/// Illustrative calls of every method, keyed by the method.
+pub fn examples(out_json_path: &str) -> Result<BTreeMap<String, Vec<String>>, Box<Error>> {
+    let method_summary = read_summary(out_json_path)?;
+    Ok(method_summary
+        .summaries
+        .iter()
+        .map(|(method, summary)| (method.clone(), method_examples(method, summary)))
+        .collect())
+}
+
+fn count_paths(summary: &json::Value, kind: &str) -> usize {
+    paths(summary, kind).len()
+}
+
+/// Notes the paths JDart did not finish exploring, because they exceed the
+/// maximum depth or the solver could not decide them, e.g.
+/// `partial (3 paths unexplored at depth 42)`, or None if there are none.
+fn method_partial(max_depth: u32, summary: &json::Value) -> Option<String> {
+    let mut reasons = Vec::new();
+    let skipped = count_paths(summary, "skippedPaths");
+    if skipped != 0 {
+        reasons.push(format!(
+            "{} path{} unexplored at depth {}",
+            skipped,
+            if skipped == 1 { "" } else { "s" },
+            max_depth
+        ));
+    }
+    let dont_know = count_paths(summary, "dontKnowPaths");
+    if dont_know != 0 {
+        reasons.push(format!(
+            "{} path{} undecided by the solver",
+            dont_know,
+            if dont_know == 1 { "" } else { "s" }
+        ));
+    }
+    if reasons.len() == 0 {
+        None
+    } else {
+        Some(format!("partial ({})", reasons.join(", ")))
+    }
+}
+
+/// Whether the results of every method, keyed by the method, may be
+/// incomplete when JPF ran with `max_depth`. See `method_partial`.
+pub fn partial(
+    max_depth: u32,
+    out_json_path: &str,
+) -> Result<BTreeMap<String, Option<String>>, Box<Error>> {
+    per_method(out_json_path, |summary| Ok(method_partial(max_depth, summary)))
+}
+
+fn has_error_paths(summary: &json::Value) -> bool {
+    count_paths(summary, "errorPaths") > 0
+}
+
+/// Applies `f` to the summary of every method, keyed by the method.
+fn per_method<T, F>(out_json_path: &str, f: F) -> Result<BTreeMap<String, T>, Box<Error>>
+where
+    F: Fn(&json::Value) -> Result<T, Box<Error>>,
+{
+    let method_summary = read_summary(out_json_path)?;
+    let mut results = BTreeMap::new();
+    for (method_name, summary) in method_summary.summaries.iter() {
+        results.insert(method_name.clone(), f(summary)?);
+    }
+    Ok(results)
+}
+
+/// The precondition of every method, keyed by the method.
+pub fn process_output(out_json_path: &str) -> Result<BTreeMap<String, String>, Box<Error>> {
+    per_method(out_json_path, method_precondition)
+}
+
+fn method_precondition(summary: &json::Value) -> Result<String, Box<Error>> {
+    let mut unparsable = Vec::new();
+    let has_error_paths = has_error_paths(summary);
+    let mut conditions = Vec::new();
+    parse_paths(summary, "okPaths", &mut conditions, &mut unparsable);
+    // group the paths by the values they fix for boolean flags and lengths
+    let mut groups = group_by_fixed_values(conditions);
+    let unconditional = groups.remove(&Vec::new());
+    if groups.len() == 0 {
+        return combine_paths(unconditional.unwrap_or_default(), &[], unparsable, has_error_paths);
+    }
+    let mut parts = Vec::new();
+    if unconditional.is_some() || unparsable.len() != 0 {
+        let c =
+            combine_paths(unconditional.unwrap_or_default(), &[], unparsable, has_error_paths)?;
+        if c == "None" {
+            return Ok(c);
+        }
+        parts.push(c);
+    }
+    for (fixed, conditions) in groups.into_iter() {
+        let c = combine_paths(conditions, &fixed, Vec::new(), has_error_paths)?;
+        parts.push(if c == "None" {
+            // the fixed values are all these paths require
+            fixed_value_conditions(&fixed).join(" && ")
+        } else {
+            format!("{}, {}", describe_fixed_values(&fixed), c)
+        });
+    }
+    Ok(parts.join("; "))
+}
+
+/// The parameters declared by any of the paths, without any constraint.
+fn parameters(paths: &[Conjunct]) -> HashMap<String, expression::Variable> {
+    let mut parameters = HashMap::new();
+    for &(ref m, _) in paths.iter() {
+        for (name, var) in m.iter() {
+            parameters
+                .entry(name.clone())
+                .or_insert_with(|| expression::Variable {
+                    name: name.clone(),
+                    typ: var.typ.clone(),
+                    range: var.typ.domain(),
+                    approximate: false,
+                });
+        }
+    }
+    parameters
+}
+
+/// Adds the parameters the path does not constrain.
+fn extend_path(
+    m: &mut HashMap<String, expression::Variable>,
+    parameters: &HashMap<String, expression::Variable>,
+) {
+    for (name, var) in parameters.iter() {
+        if !m.contains_key(name) {
+            m.insert(name.clone(), var.clone());
+        }
+    }
+}
+
+/// Splits the part of the box `path` outside the error path `error` into
+/// disjoint boxes. Sets `approximate` if the result may include inputs of the
+/// error path.
+fn subtract_path(path: &Conjunct, error: &Conjunct, approximate: &mut bool) -> Vec<Conjunct> {
+    let mut pieces = Vec::new();
+    // the part of the path inside the error path so far
+    let mut inside = path.clone();
+    let mut names: Vec<&String> = error.0.keys().collect();
+    names.sort();
+    for name in names.into_iter() {
+        let var = &error.0[name];
+        let range = inside.0[name].range.clone();
+        let outside = range.difference(&var.range);
+        if outside.is_approximate() {
+            *approximate = true;
+        }
+        if !outside.is_empty() {
+            let mut piece = inside.clone();
+            piece.0.get_mut(name).unwrap().range = outside;
+            pieces.push(piece);
+        }
+        let common = range.intersect(&var.range);
+        if common.is_empty() {
+            return pieces;
+        }
+        let inside_var = inside.0.get_mut(name).unwrap();
+        inside_var.range = common;
+        // a smaller error region only makes the safe region smaller
+        inside_var.approximate |= var.approximate;
+    }
+    for relation in error.1.iter() {
+        let mut piece = inside.clone();
+        piece.1.push(relation.negation(&error.0));
+        pieces.push(piece);
+        inside.1.push(relation.clone());
+    }
+    pieces
+}
+
+/// The inputs for which the method does not end in an error, i.e. the domain
+/// of its parameters without the regions of the error paths.
+pub fn safe_precondition(out_json_path: &str) -> Result<BTreeMap<String, String>, Box<Error>> {
+    per_method(out_json_path, method_safe_precondition)
+}
+
+fn method_safe_precondition(summary: &json::Value) -> Result<String, Box<Error>> {
+    let mut ok_conditions = Vec::new();
+    let mut errors = Vec::new();
+    let mut unparsable = Vec::new();
+    parse_paths(summary, "okPaths", &mut ok_conditions, &mut Vec::new());
+    parse_paths(summary, "errorPaths", &mut errors, &mut unparsable);
+    if errors.len() == 0 && unparsable.len() == 0 {
+        return Ok(String::from("None"));
+    }
+    let mut error_paths: Vec<Conjunct> = Vec::new();
+    for condition in errors.into_iter() {
+        match condition {
+            Condition::True => return Ok(String::from("No satisfiable value")),
+            Condition::Conditions(m, relations) => error_paths.push((m, relations)),
+            Condition::Disjunction(_) => unreachable!(),
+        }
+    }
+    // the paths declare the parameters they depend on
+    let mut declared: Vec<Conjunct> = ok_conditions
+        .into_iter()
+        .filter_map(|c| match c {
+            Condition::Conditions(m, _) => Some((m, Vec::new())),
+            _ => None,
+        })
+        .collect();
+    declared.extend(error_paths.iter().cloned());
+    let parameters = parameters(&declared);
+    let mut approximate = false;
+    let mut safe: Vec<Conjunct> = vec![(parameters.clone(), Vec::new())];
+    for mut error in error_paths.into_iter() {
+        extend_path(&mut error.0, &parameters);
+        let mut pieces = Vec::new();
+        for path in safe.iter() {
+            pieces.extend(subtract_path(path, &error, &mut approximate));
+        }
+        expression::merge_disjunction(&mut pieces);
+        safe = pieces;
+    }
+    if safe.len() == 0 {
+        return Ok(String::from("No satisfiable value"));
+    }
+    let safe = safe.into_iter()
+        .map(|(m, relations)| Condition::Conditions(m, relations))
+        .collect();
+    let mut c = combine_paths(safe, &[], Vec::new(), false)?;
+    if approximate && !c.ends_with(" (approximate)") {
+        c.push_str(" (approximate)");
+    }
+    if unparsable.len() == 0 {
+        return Ok(c);
+    }
+    // the error paths that could not be parsed must not be taken
+    let mut conditions: Vec<String> = unparsable.iter().map(|s| format!("!({})", s)).collect();
+    if c != "None" {
+        conditions.insert(0, if is_parenthesized(&c) || !c.contains(" || ") {
+            c
+        } else {
+            format!("({})", c)
+        });
+    }
+    Ok(conditions.join(" && "))
+}
+
+/// Whether the condition is enclosed in a single pair of parentheses.
+fn is_parenthesized(c: &str) -> bool {
+    let mut depth = 0;
+    for (i, ch) in c.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => (),
+        }
+        if depth == 0 && i + ch.len_utf8() < c.len() {
+            return false;
+        }
+    }
+    c.starts_with('(') && depth == 0
+}
+
+/// Combines the conditions of the ok paths, and the ones that could not be
+/// parsed, into a single precondition. The paths share the `fixed` values.
+/// Paths relating variables are joined as difference-bound matrices where the
+/// join is exact, as an inexact one would also admit inputs no path takes.
+fn combine_paths(
+    conditions: Vec<Condition>,
+    fixed: &[FixedValue],
+    mut unparsable: Vec<String>,
+    has_error_paths: bool,
+) -> Result<String, Box<Error>> {
+    let mut paths: Vec<Conjunct> = Vec::new();
+    for condition in conditions.into_iter() {
+        match condition {
+            Condition::True => return Ok(String::from("None")),
+            Condition::Conditions(m, relations) => {
+                // infeasible paths do not add any value
+                if m.values().all(|var| !var.range.is_empty()) {
+                    paths.push((m, relations));
+                }
+            }
+            Condition::Disjunction(_) => unreachable!(),
+        }
+    }
+    if unparsable.len() == 0 && paths.len() == 0 {
         return Ok(String::from(if has_error_paths {
             "None"
         } else {
             "No satisfiable value"
         }));
     }
-    let single_var_conditions = variable_conditions_to_string(&parsable_with_one_variable)?;
-    if single_var_conditions.len() != 0 {
-        unparsable.push(single_var_conditions);
+    // extend every path to a box over all parameters, so that paths
+    // constraining different parameters are not mixed up
+    let parameters = parameters(&paths);
+    for &mut (ref mut m, _) in paths.iter_mut() {
+        extend_path(m, &parameters);
     }
-    for cond in parsable_with_multiple_variables.iter() {
-        unparsable.push(variable_conditions_to_string(cond)?);
+    expression::merge_disjunction(&mut paths);
+    // some narrowing casts or unions could not be represented exactly
+    let approximate = paths
+        .iter()
+        .flat_map(|&(ref m, _)| m.values())
+        .any(|var| var.approximate || var.range.is_approximate());
+    // conditions repeated for the elements of arrays are stated once
+    let mut paths = quantifier::quantify(paths, fixed)?;
+    let mut dbms: Vec<Option<Dbm>> = paths
+        .iter()
+        .map(|&((ref m, ref relations), _)| if relations.len() == 0 {
+            None
+        } else {
+            path_to_dbm(m, relations)
+        })
+        .collect();
+    // paths whose join holds exactly when either does are joined, e.g.
+    // `i <= j && n > 0` and `i > j && n > 0` to `n > 0`, until no more can be
+    let mut joined = true;
+    while joined {
+        joined = false;
+        'pairs: for i in 0..dbms.len() {
+            for j in i + 1..dbms.len() {
+                let join = match (&dbms[i], &dbms[j]) {
+                    (&Some(ref a), &Some(ref b))
+                        if (paths[i].1).len() == 0 && (paths[j].1).len() == 0
+                            && a.get_names() == b.get_names() =>
+                    {
+                        a.exact_join(b)
+                    }
+                    _ => None,
+                };
+                if let Some(join) = join {
+                    dbms[i] = Some(join);
+                    dbms.remove(j);
+                    paths.remove(j);
+                    joined = true;
+                    break 'pairs;
+                }
+            }
+        }
     }
-    if unparsable.len() == 1 {
-        return Ok(unparsable[0].clone());
+    let mut holds_always = false;
+    for (i, &((ref m, ref relations), ref statements)) in paths.iter().enumerate() {
+        let cond = match dbms[i] {
+            Some(ref dbm) => {
+                // paths that are infeasible or implied by another path add
+                // nothing to the disjunction
+                let subsumed = dbms.iter().enumerate().any(|(j, other)| match *other {
+                    Some(ref other) if j != i && other.get_names() == dbm.get_names() => {
+                        other.includes(dbm) && (j < i || !dbm.includes(other))
+                    }
+                    _ => false,
+                });
+                if dbm.is_empty() || subsumed {
+                    continue;
+                }
+                dbm.conditions().join(" && ")
+            }
+            None => conditions_to_string(m, relations)?,
+        };
+        let cond = if statements.len() == 0 {
+            cond
+        } else if cond.len() == 0 && statements.len() == 1 {
+            statements[0].clone()
+        } else {
+            let mut conditions: Vec<String> =
+                statements.iter().map(|s| format!("({})", s)).collect();
+            if cond.len() != 0 {
+                conditions.insert(0, cond);
+            }
+            conditions.join(" && ")
+        };
+        if cond.len() == 0 {
+            // this path holds for every input
+            holds_always = true;
+            break;
+        }
+        unparsable.push(cond);
     }
-    let ret = unparsable.join(") || (");
-    if ret == "" {
-        return Ok(String::from("None"));
+    let ret = if holds_always {
+        String::from("None")
+    } else if unparsable.len() == 0 {
+        // every path turned out to be infeasible
+        String::from(if has_error_paths {
+            "None"
+        } else {
+            "No satisfiable value"
+        })
+    } else if unparsable.len() == 1 {
+        unparsable[0].clone()
+    } else {
+        unparsable
+            .iter()
+            .map(|c| if is_parenthesized(c) {
+                c.clone()
+            } else {
+                format!("({})", c)
+            })
+            .collect::<Vec<String>>()
+            .join(" || ")
+    };
+    if approximate {
+        return Ok(format!("{} (approximate)", ret));
     }
-    Ok(format!("({})", ret))
+    Ok(ret)
+}
+
+/// The names of the parameters in a signature as JDart expects it whose type
+/// satisfies `is_type`, e.g. `xs` for `pkg.C.count(xs:int[],)` and arrays.
+fn parameters_of_type<F>(method_signature: &str, is_type: F) -> Vec<String>
+where
+    F: Fn(&str) -> bool,
+{
+    signature_parameters(method_signature)
+        .into_iter()
+        .filter(|&(_, ref typ)| is_type(typ))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// The names and types of the parameters in a signature as JDart expects it,
+/// e.g. `xs` and `int[]` for `pkg.C.count(xs:int[],)`.
+fn signature_parameters(method_signature: &str) -> Vec<(String, String)> {
+    let start = method_signature.find('(').map_or(0, |i| i + 1);
+    let end = method_signature.rfind(')').unwrap_or(method_signature.len());
+    method_signature[start..end]
+        .split(',')
+        .filter_map(|param| {
+            let mut it = param.splitn(2, ':');
+            match (it.next(), it.next()) {
+                (Some(name), Some(typ)) => Some((String::from(name), String::from(typ))),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn array_parameters(method_signature: &str) -> Vec<String> {
+    parameters_of_type(method_signature, |typ| typ.ends_with("[]"))
+}
+
+fn string_parameters(method_signature: &str) -> Vec<String> {
+    parameters_of_type(method_signature, |typ| {
+        typ == "String" || typ == "java.lang.String"
+    })
 }
 
+/// The conditions on the inputs of `method` JDart assumes, from the
+/// configuration, the bounds on the length of arrays and strings and, if
+/// enabled, the Javadoc of the method. They refer to the parameters of the
+/// original method even if JDart explores a driver.
+pub fn assumptions(
+    config: &Config,
+    package: &str,
+    class: &str,
+    method: &str,
+) -> Result<Vec<String>, Box<Error>> {
+    let (method_name, method_signature) = super::parse_java_method(package, class, method)?;
+    let options = config.jdart_options(&method_name);
+    let mut constraints = options.constraints.unwrap_or_default();
+    if let Some(max_length) = options.max_array_length {
+        for name in array_parameters(&method_signature).iter() {
+            constraints.push(format!("{}.length <= {}", name, max_length));
+        }
+    }
+    if let Some(max_length) = options.max_string_length {
+        for name in string_parameters(&method_signature).iter() {
+            constraints.push(format!("{}.length() <= {}", name, max_length));
+        }
+    }
+    if options.constraints_from_javadoc.unwrap_or(false) {
+        let relative_path = format!("{}/{}.java", package.replace('.', "/"), class);
+        for classpath in config.classpath.iter() {
+            let source_path = construct_path(&PathBuf::from(classpath), &relative_path)?;
+            let source = match super::read_file_to_string(&source_path) {
+                Ok(source) => source,
+                Err(_) => continue,
+            };
+            if let Some(doc) = javadoc::find_javadoc(&source, &method_name) {
+                let parameters = signature_parameters(&method_signature);
+                constraints.extend(javadoc::assumptions(doc, &parameters));
+            }
+            break;
+        }
+    }
+    Ok(constraints)
+}
+
+/// The maximum depth JDart explores `method` to.
+pub fn max_depth(config: &Config, package: &str, class: &str, method: &str) -> Result<u32, Box<Error>> {
+    let (method_name, _) = super::parse_java_method(package, class, method)?;
+    Ok(config
+        .jdart_options(&method_name)
+        .max_depth
+        .unwrap_or(config.max_depth))
+}
+
+/// Writes the configuration of JPF exploring `method`, assuming the
+/// `constraints` on its parameters, see `assumptions`.
 pub fn setup_environment(
     config: &Config,
     output_path: &PathBuf,
     package: &str,
     class: &str,
     method: &str,
+    constraints: &[String],
+) -> Result<(String, process::Command), Box<Error>> {
+    let max_depth = max_depth(config, package, class, method)?;
+    setup_environment_at_depth(config, output_path, package, class, method, constraints, max_depth)
+}
+
+fn setup_environment_at_depth(
+    config: &Config,
+    output_path: &PathBuf,
+    package: &str,
+    class: &str,
+    method: &str,
+    constraints: &[String],
+    max_depth: u32,
 ) -> Result<(String, process::Command), Box<Error>> {
     lazy_static! {
         static ref TEMPLATE: mustache::Template = mustache::compile_str(SPF_TEMPLATE).unwrap();
     }
     let (method_name, method_signature) = super::parse_java_method(package, class, method)?;
+    let options = config.jdart_options(&method_name);
+    let target = format!("{}.{}", package, class);
+    let include = options
+        .symbolic_include
+        .unwrap_or_else(|| vec![String::from("this.*"), format!("{}.*", target)]);
+    let statics = options.symbolic_statics.unwrap_or_else(|| vec![target.clone()]);
+    let exclude = options.symbolic_exclude.unwrap_or_default();
+    let properties = options.properties;
     let jar_path = construct_path(&PathBuf::from(&config.jpf_home), "build/RunJPF.jar")?;
     let out_json_path = construct_path(output_path, "out.json")?;
     let run_jpf_path = construct_path(output_path, "run.jpf")?;
@@ -213,7 +1339,22 @@ pub fn setup_environment(
         .insert_str("class", class)
         .insert_str("method_name", method_name)
         .insert_str("method_signature", method_signature)
-        .insert_str("max_depth", format!("{}", config.max_depth))
+        .insert_str("max_depth", format!("{}", max_depth))
+        .insert_str("solver", options.solver.unwrap_or_else(|| String::from("z3")))
+        .insert_str("bitvectors", format!("{}", options.bitvectors.unwrap_or(true)))
+        .insert_str("include", include.join(";"))
+        .insert_str("statics", statics.join(";"))
+        .insert_bool("has_constraints", constraints.len() != 0)
+        .insert_str("constraints", constraints.join(";"))
+        .insert_bool("has_exclude", exclude.len() != 0)
+        .insert_str("exclude", exclude.join(";"))
+        .insert_vec("properties", |mut builder| {
+            for (key, value) in properties.iter() {
+                builder =
+                    builder.push_map(|map| map.insert_str("key", key).insert_str("value", value));
+            }
+            builder
+        })
         .build();
     let mut run_jpf_file = File::create(&run_jpf_path)?;
     TEMPLATE.render_data(&mut run_jpf_file, &template_args)?;
@@ -227,3 +1368,582 @@ pub fn setup_environment(
         .args(&args);
     Ok((out_json_path, cmd))
 }
+
+/// What to do after a run of JPF in `deepen`.
+#[derive(Debug, PartialEq)]
+enum Step {
+    /// the results are the same as the ones of the previous run
+    Converged,
+    /// the budget is spent or the depth cannot grow any further
+    Stop,
+    /// run again with the given depth
+    Deepen(u32),
+}
+
+/// Decides how to go on after a run at `depth` gave `preconditions`, given the
+/// preconditions of the previous run, if any.
+fn next_step(
+    previous: Option<&BTreeMap<String, String>>,
+    preconditions: &BTreeMap<String, String>,
+    depth: u32,
+    budget_spent: bool,
+) -> Step {
+    if previous == Some(preconditions) {
+        return Step::Converged;
+    }
+    match depth.checked_mul(2) {
+        Some(next) if !budget_spent => Step::Deepen(next),
+        _ => Step::Stop,
+    }
+}
+
+/// Runs JPF with the configured maximum depth, and then with twice the depth
+/// of the previous run, until the preconditions stop changing or `budget` is
+/// spent. A run still going when the budget is spent is stopped.
+pub fn deepen(
+    config: &Config,
+    output_path: &PathBuf,
+    package: &str,
+    class: &str,
+    method: &str,
+    constraints: &[String],
+    budget: Duration,
+) -> Result<Deepening, Box<Error>> {
+    let start = Instant::now();
+    let mut depth = max_depth(config, package, class, method)?;
+    let mut previous: Option<(Deepening, BTreeMap<String, String>)> = None;
+    loop {
+        let depth_path = output_path.join(format!("depth-{}", depth));
+        fs::create_dir_all(&depth_path)?;
+        let (out_json_path, mut cmd) = setup_environment_at_depth(
+            config,
+            &depth_path,
+            package,
+            class,
+            method,
+            constraints,
+            depth,
+        )?;
+        let mut jpf = cmd.spawn()?;
+        let status = loop {
+            if let Some(status) = jpf.try_wait()? {
+                break Some(status);
+            }
+            // the first run is needed for any result at all
+            if previous.is_some() && start.elapsed() >= budget {
+                jpf.kill()?;
+                jpf.wait()?;
+                break None;
+            }
+            thread::sleep(Duration::from_millis(100));
+        };
+        match status {
+            Some(ref status) if status.success() => (),
+            Some(_) => return Err(Box::new(JpfFailed::at_depth(depth))),
+            None => return Ok(previous.unwrap().0),
+        }
+        let preconditions = process_output(&out_json_path)?;
+        let step = next_step(
+            previous.as_ref().map(|&(_, ref p)| p),
+            &preconditions,
+            depth,
+            start.elapsed() >= budget,
+        );
+        let deepening = Deepening {
+            out_json_path,
+            depth,
+            converged: false,
+        };
+        match step {
+            Step::Converged => {
+                let mut deepening = previous.unwrap().0;
+                deepening.converged = true;
+                return Ok(deepening);
+            }
+            Step::Stop => return Ok(deepening),
+            Step::Deepen(next) => {
+                previous = Some((deepening, preconditions));
+                depth = next;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs::File;
+    use std::io::Write;
+
+    use super::driver::Driver;
+    use super::expression::Type;
+    use super::super::{construct_path, create_random_path, json, read_file_to_string, Config};
+    use super::super::range::Range;
+    use super::{assumptions, count_paths, method_examples, method_partial, method_precondition,
+                method_returns, method_safe_precondition, method_throws, next_step, partial,
+                per_method, process_output, ranges_to_string, returns, safe_precondition,
+                setup_environment, throws, Step};
+
+    /// Writes the summaries of methods as JDart would and returns the path of
+    /// the file.
+    fn write_summaries(summaries: &str) -> String {
+        let dir = create_random_path(&env::temp_dir(), "doco-test", 16).unwrap();
+        let path = construct_path(&dir, "out.json").unwrap();
+        let mut file = File::create(&path).unwrap();
+        write!(file, r#"{{"summaries": {{{}}}}}"#, summaries).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_ranges_to_string() {
+        let describe = |range: Range| ranges_to_string(&range, "n", &Type::SInt32).unwrap();
+        assert_eq!("", describe(Type::SInt32.range()));
+        assert_eq!("n >= 1", describe(Range::from(1, i32::max_value() as i128)));
+        assert_eq!("n >= 0 && n <= 100", describe(Range::from(0, 100)));
+        let outside = Range::from(i32::min_value() as i128, -1)
+            .union(&Range::from(101, i32::max_value() as i128));
+        assert_eq!("n < 0 || n > 100", describe(outside));
+        let gap = Range::from(i32::min_value() as i128, 4)
+            .union(&Range::from(6, i32::max_value() as i128));
+        assert_eq!("n != 5", describe(gap));
+        let intervals = Range::from(0, 10).union(&Range::from(20, 20));
+        assert_eq!("(n >= 0 && n <= 10) || n == 20", describe(intervals.clone()));
+        let even = intervals.intersect(&Range::congruent(2, 0));
+        assert_eq!("n % 2 == 0 && ((n >= 0 && n <= 10) || n == 20)", describe(even));
+        assert_eq!(
+            "n % 2 == 0 && n >= 0 && n <= 10",
+            describe(Range::from(0, 10).intersect(&Range::congruent(2, 0)))
+        );
+        assert!(ranges_to_string(&Range::from(1, 0), "n", &Type::SInt32).is_err());
+    }
+
+    #[test]
+    fn test_fixed_boolean() {
+        let summary = json::from_str(
+            r#"{
+                "okPaths": [
+                    {
+                        "pathCondition": "[L]declare 'flag':bool in (('flag' == true))",
+                        "result": "1"
+                    }
+                ],
+                "errorPaths": [
+                    {
+                        "pathCondition": "[L]declare 'flag':bool in (('flag' == false))",
+                        "exceptionClass": "java.lang.IllegalArgumentException"
+                    }
+                ]
+            }"#,
+        ).unwrap();
+        assert_eq!("flag == true", method_precondition(&summary).unwrap());
+        assert_eq!("returns 1 when flag == true", method_returns(&summary).unwrap());
+        let summary = json::from_str(
+            r#"{
+                "okPaths": [
+                    {
+                        "pathCondition": "[L]declare 'flag':bool, 'n':sint32 in (('flag' == true) && ('n' > 0))",
+                        "result": "1"
+                    }
+                ]
+            }"#,
+        ).unwrap();
+        assert_eq!(
+            "when flag == true, n >= 1",
+            method_precondition(&summary).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_complementary_paths() {
+        let path = write_summaries(
+            r#""examples.IsPositive.isPositive(other_state:int,i:int)": {
+                "okPaths": [
+                    {
+                        "pathCondition": "[L]declare 'i':sint32, 'other_state':sint32 in (('i' > 'other_state'))",
+                        "result": "1"
+                    },
+                    {
+                        "pathCondition": "[L]declare 'i':sint32, 'other_state':sint32 in (('i' <= 'other_state'))",
+                        "result": "0"
+                    }
+                ]
+            }"#,
+        );
+        let preconditions = process_output(&path).unwrap();
+        assert_eq!(
+            Some(&String::from("None")),
+            preconditions.get("examples.IsPositive.isPositive(other_state:int,i:int)")
+        );
+        let summary = json::from_str(
+            r#"{
+                "okPaths": [
+                    {
+                        "pathCondition": "[L]declare 'i':sint32, 'j':sint32 in (('i' < 'j'))",
+                        "result": "1"
+                    },
+                    {
+                        "pathCondition": "[L]declare 'i':sint32, 'j':sint32 in (('i' >= ('j' + 2)))",
+                        "result": "0"
+                    }
+                ]
+            }"#,
+        ).unwrap();
+        // j <= i < j + 2 is not covered
+        assert_eq!("(i < j) || (i >= j + 2)", method_precondition(&summary).unwrap());
+    }
+
+    #[test]
+    fn test_joined_paths() {
+        let summary = json::from_str(
+            r#"{
+                "okPaths": [
+                    {
+                        "pathCondition": "[L]declare 'i':sint32, 'j':sint32, 'n':sint32 in (('i' < 'j') && ('n' > 0))",
+                        "result": "1"
+                    },
+                    {
+                        "pathCondition": "[L]declare 'i':sint32, 'j':sint32, 'n':sint32 in (('i' >= 'j') && ('n' > 0))",
+                        "result": "0"
+                    },
+                    {
+                        "pathCondition": "[L]declare 'i':sint32, 'j':sint32, 'n':sint32 in (('i' == 'j') && ('n' < -5))",
+                        "result": "2"
+                    }
+                ]
+            }"#,
+        ).unwrap();
+        // the first two only differ in whether i < j
+        assert_eq!("(n >= 1) || (i == j && n <= -6)", method_precondition(&summary).unwrap());
+    }
+
+    #[test]
+    fn test_missing_paths() {
+        // neither errorPaths nor the lists of unfinished paths
+        let path = write_summaries(
+            r#""examples.Silly.abs(n:int)": {
+                "okPaths": [
+                    {
+                        "pathCondition": "[L]declare 'n':sint32 in (('n' >= 0))",
+                        "result": "n"
+                    }
+                ]
+            }"#,
+        );
+        let method = "examples.Silly.abs(n:int)";
+        assert_eq!("n >= 0", process_output(&path).unwrap()[method]);
+        assert_eq!(Vec::<String>::new(), throws(&path).unwrap()[method]);
+        assert_eq!("None", safe_precondition(&path).unwrap()[method]);
+        let summary = json::from_str(r#"{"okPaths": null, "errorPaths": {}}"#).unwrap();
+        assert_eq!("No satisfiable value", method_precondition(&summary).unwrap());
+        assert_eq!(Vec::<String>::new(), method_throws(&summary).unwrap());
+        assert_eq!("None", method_safe_precondition(&summary).unwrap());
+    }
+
+    #[test]
+    fn test_method_throws() {
+        let summary = json::from_str(
+            r#"{
+                "okPaths": [],
+                "errorPaths": [
+                    {
+                        "pathCondition": "[L]declare 'a':sint32, 'b':sint32 in (('a' < 0))",
+                        "exceptionClass": "java.lang.IllegalArgumentException"
+                    },
+                    {
+                        "pathCondition": "[L]declare 'a':sint32, 'b':sint32 in (('a' >= 0) && ('b' == 0))",
+                        "exceptionClass": "java.lang.ArithmeticException"
+                    },
+                    {
+                        "pathCondition": "[L]declare 'a':sint32, 'b':sint32 in (('a' > 100))",
+                        "exceptionClass": "java.lang.IllegalArgumentException"
+                    }
+                ]
+            }"#,
+        ).unwrap();
+        assert_eq!(
+            vec![
+                "throws ArithmeticException when a >= 0 && b == 0",
+                "throws IllegalArgumentException when a < 0 || a > 100",
+            ],
+            method_throws(&summary).unwrap()
+        );
+        let summary = json::from_str(
+            r#"{
+                "okPaths": [],
+                "errorPaths": [
+                    {
+                        "pathCondition": "[L]declare 'strict':bool, 'n':sint32 in (('strict' == true) && ('n' < 0))",
+                        "exceptionClass": "java.lang.IllegalStateException"
+                    },
+                    {
+                        "pathCondition": "[L]true"
+                    },
+                    {
+                        "pathCondition": "[L]declare 'n':sint32 in (('n' ? 2))",
+                        "exceptionClass": "java.lang.IllegalStateException"
+                    }
+                ]
+            }"#,
+        ).unwrap();
+        assert_eq!(
+            vec![
+                "throws Exception",
+                "when strict == true, throws IllegalStateException when n <= -1",
+                "throws IllegalStateException when (('n' ? 2))",
+            ],
+            method_throws(&summary).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_method_examples() {
+        let summary = json::from_str::<json::Value>(
+            r#"{
+                "okPaths": [
+                    {
+                        "pathCondition": "[L]declare 'xs.length':sint32, 'xs[0]':sint32, 'xs[1]':sint32 in (('xs.length' == 2) && ('xs[0]' > 0) && ('xs[1]' > 0))",
+                        "valuation": "'xs.length':=2,'xs[0]':=1,'xs[1]':=1",
+                        "result": "2"
+                    },
+                    {
+                        "pathCondition": "[L]declare 'xs.length':sint32, 'this.state':sint32 in (('xs.length' == 0) && ('this.state' == 3))",
+                        "result": "0"
+                    }
+                ]
+            }"#,
+        ).unwrap();
+        // the lengths and elements make up the arrays, the fields follow
+        assert_eq!(
+            vec![
+                "countPositives(new int[]{}) with this.state == 3 returns 0",
+                "countPositives(new int[]{1, 1}) returns 2",
+            ],
+            method_examples("examples.Silly.countPositives(xs:int[],)", &summary)
+        );
+    }
+
+    #[test]
+    fn test_method_returns() {
+        let summary = |extra: &str| {
+            json::from_str::<json::Value>(&format!(
+                r#"{{
+                    "okPaths": [
+                        {{
+                            "pathCondition": "[L]declare 'n':sint32 in (('n' < 0))",
+                            "result": "-1"
+                        }},
+                        {{
+                            "pathCondition": "[L]declare 'n':sint32 in (('n' == 0))",
+                            "result": "0"
+                        }},
+                        {{
+                            "pathCondition": "[L]declare 'n':sint32 in (('n' > 0) && ('n' < 10))",
+                            "result": "-1"
+                        }}
+                    ]{}
+                }}"#,
+                extra
+            )).unwrap()
+        };
+        // -1 is returned on most paths
+        assert_eq!(
+            "returns 0 when n == 0, returns -1 otherwise",
+            method_returns(&summary("")).unwrap()
+        );
+        let partial = "returns -1 when n < 0 || (n >= 1 && n <= 9), returns 0 when n == 0";
+        let rest = r#"[{"pathCondition": "[L]declare 'n':sint32 in (('n' >= 10))"}]"#;
+        for kind in ["skippedPaths", "dontKnowPaths", "errorPaths"].iter() {
+            let summary = summary(&format!(r#", "{}": {}"#, kind, rest));
+            assert_eq!(partial, method_returns(&summary).unwrap());
+        }
+        let void = json::from_str(r#"{"okPaths": [{"pathCondition": "[L]true"}]}"#).unwrap();
+        assert_eq!("", method_returns(&void).unwrap());
+    }
+
+    #[test]
+    fn test_per_method() {
+        let path = write_summaries(
+            r#""examples.Silly.abs(n:int)": {
+                "okPaths": [
+                    {
+                        "pathCondition": "[L]declare 'n':sint32 in (('n' >= 0))",
+                        "result": "'n'"
+                    },
+                    {
+                        "pathCondition": "[L]declare 'n':sint32 in (('n' < 0))",
+                        "result": "-'n'"
+                    }
+                ]
+            },
+            "examples.Silly.check(n:int)": {
+                "okPaths": [],
+                "errorPaths": [
+                    {
+                        "pathCondition": "[L]declare 'n':sint32 in (('n' != 0))",
+                        "exceptionClass": "java.lang.IllegalArgumentException"
+                    }
+                ]
+            }"#,
+        );
+        let counts = per_method(&path, |summary| Ok(count_paths(summary, "okPaths"))).unwrap();
+        assert_eq!(
+            vec![
+                (String::from("examples.Silly.abs(n:int)"), 2),
+                (String::from("examples.Silly.check(n:int)"), 0),
+            ],
+            counts.into_iter().collect::<Vec<(String, usize)>>()
+        );
+        let returns = returns(&path).unwrap();
+        assert_eq!(
+            "returns n when n >= 0, returns -n otherwise",
+            returns["examples.Silly.abs(n:int)"]
+        );
+        assert_eq!("", returns["examples.Silly.check(n:int)"]);
+        let preconditions = process_output(&path).unwrap();
+        assert_eq!("None", preconditions["examples.Silly.abs(n:int)"]);
+        assert_eq!("None", preconditions["examples.Silly.check(n:int)"]);
+        // the first error of any method is the result
+        assert!(per_method(&path, |_| Err::<(), _>(From::from("failed"))).is_err());
+        assert!(per_method("/nonexistent/out.json", |_| Ok(())).is_err());
+    }
+
+    #[test]
+    fn test_partial() {
+        let path = r#"{"pathCondition": "[L]declare 'n':sint32 in (('n' > 0))"}"#;
+        let summary = json::from_str(&format!(
+            r#"{{"okPaths": [{}], "skippedPaths": [{}, {}, {}], "dontKnowPaths": [{}]}}"#,
+            path, path, path, path, path
+        )).unwrap();
+        assert_eq!(
+            Some(String::from(
+                "partial (3 paths unexplored at depth 42, 1 path undecided by the solver)"
+            )),
+            method_partial(42, &summary)
+        );
+        let summary = json::from_str(&format!(r#"{{"skippedPaths": [{}]}}"#, path)).unwrap();
+        assert_eq!(
+            Some(String::from("partial (1 path unexplored at depth 7)")),
+            method_partial(7, &summary)
+        );
+        let summary = json::from_str(&format!(r#"{{"okPaths": [{}]}}"#, path)).unwrap();
+        assert_eq!(None, method_partial(42, &summary));
+        let path = write_summaries(&format!(
+            r#""examples.Silly.f(n:int)": {{"okPaths": [{}], "dontKnowPaths": [{}]}},
+            "examples.Silly.g(n:int)": {{"okPaths": [{}]}}"#,
+            path, path, path
+        ));
+        let partial = partial(10, &path).unwrap();
+        assert_eq!(
+            Some(String::from("partial (1 path undecided by the solver)")),
+            partial["examples.Silly.f(n:int)"]
+        );
+        assert_eq!(None, partial["examples.Silly.g(n:int)"]);
+    }
+
+    #[test]
+    fn test_deepen_steps() {
+        let summaries = |bound: i32| {
+            write_summaries(&format!(
+                r#""examples.Silly.f(n:int)": {{
+                    "okPaths": [
+                        {{"pathCondition": "[L]declare 'n':sint32 in (('n' < {}))"}}
+                    ],
+                    "skippedPaths": [
+                        {{"pathCondition": "[L]declare 'n':sint32 in (('n' >= {}))"}}
+                    ]
+                }}"#,
+                bound, bound
+            ))
+        };
+        // deeper runs explore more paths until the results stop changing
+        let at_10 = process_output(&summaries(10)).unwrap();
+        let at_20 = process_output(&summaries(20)).unwrap();
+        let at_40 = process_output(&summaries(20)).unwrap();
+        assert_eq!(Step::Deepen(20), next_step(None, &at_10, 10, false));
+        assert_eq!(Step::Deepen(40), next_step(Some(&at_10), &at_20, 20, false));
+        assert_eq!(Step::Converged, next_step(Some(&at_20), &at_40, 40, false));
+        // convergence is recognized even once the budget is spent
+        assert_eq!(Step::Converged, next_step(Some(&at_20), &at_40, 40, true));
+        assert_eq!(Step::Stop, next_step(Some(&at_10), &at_20, 20, true));
+        assert_eq!(Step::Stop, next_step(None, &at_10, 1 << 31, false));
+    }
+
+    #[test]
+    fn test_run_jpf() {
+        let mut config = Config::from_str(
+            r#"{
+                "jpf_home": "jpf",
+                "jvm_flags": "-ea",
+                "classpath": ["classes"],
+                "daikon_classpath": [],
+                "max_depth": 42,
+                "jdart": {
+                    "constraints": ["n >= 0"],
+                    "max_array_length": 5
+                },
+                "methods": {
+                    "count": {
+                        "solver": "coral",
+                        "max_depth": 7,
+                        "symbolic_exclude": ["this.cache"],
+                        "constraints": ["n < xs.length"],
+                        "properties": {"search.multiple_errors": "true"}
+                    }
+                }
+            }"#,
+        ).unwrap();
+        config.add_constraints(&[String::from("n != 3")]);
+        let dir = create_random_path(&env::temp_dir(), "doco-test", 16).unwrap();
+        let method = "int count(int[] xs, int n)";
+        let constraints = assumptions(&config, "examples", "Silly", method).unwrap();
+        let (out_json_path, _) =
+            setup_environment(&config, &dir, "examples", "Silly", method, &constraints).unwrap();
+        assert_eq!(construct_path(&dir, "out.json").unwrap(), out_json_path);
+        let run_jpf = read_file_to_string(&construct_path(&dir, "run.jpf").unwrap()).unwrap();
+        let lines: Vec<&str> = run_jpf.lines().collect();
+        for line in [
+            "symbolic.dp=coral",
+            "symbolic.dp.coral.bitvectors=true",
+            "target=examples.Silly",
+            "classpath=classes",
+            "concolic.method.count=examples.Silly.count(xs:int[],n:int,)",
+            "jdart.configs.count.symbolic.statics=examples.Silly",
+            "jdart.configs.count.symbolic.include=this.*;examples.Silly.*",
+            "concolic.method.count.constraints=n >= 0;n != 3;n < xs.length;xs.length <= 5",
+            "jdart.configs.count.symbolic.exclude=this.cache",
+            "jdart.configs.count.max_depth=7",
+            "search.multiple_errors=true",
+        ].iter()
+        {
+            assert!(lines.contains(line), "{} missing from {}", line, run_jpf);
+        }
+    }
+
+    #[test]
+    fn test_run_jpf_with_driver() {
+        let config = Config::from_str(
+            r#"{
+                "jpf_home": "jpf",
+                "jvm_flags": "-ea",
+                "classpath": ["classes"],
+                "daikon_classpath": [],
+                "max_depth": 42,
+                "jdart": {"max_string_length": 4}
+            }"#,
+        ).unwrap();
+        let dir = create_random_path(&env::temp_dir(), "doco-test", 16).unwrap();
+        let method = "public static String repeat(String s, int n)";
+        let constraints = assumptions(&config, "examples", "GuavaStrings", method).unwrap();
+        assert_eq!(vec!["s.length() <= 4"], constraints);
+        let driver = Driver::generate(&config, &dir, "examples", "GuavaStrings", method)
+            .unwrap()
+            .unwrap();
+        // the driver takes the characters of the string as an array
+        let constraints: Vec<String> = constraints.iter().map(|c| driver.encode(c)).collect();
+        setup_environment(&config, &dir, "examples", &driver.class, &driver.method, &constraints)
+            .unwrap();
+        let run_jpf = read_file_to_string(&construct_path(&dir, "run.jpf").unwrap()).unwrap();
+        let line = "concolic.method.repeat.constraints=s$chars.length <= 4";
+        assert!(run_jpf.lines().any(|l| l == line), "{} missing from {}", line, run_jpf);
+    }
+}
@@ -0,0 +1,263 @@
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::error::Error;
+
+use super::FixedValue;
+use super::expression::{Conjunct, Domain, Type, Variable};
+
+/// The length of the array `base` if it is fixed, either for the group of
+/// paths or by the path itself.
+fn length(m: &HashMap<String, Variable>, fixed: &[FixedValue], base: &str) -> Option<usize> {
+    let name = format!("{}.length", base);
+    fixed
+        .iter()
+        .find(|&&(ref fixed, _)| *fixed == name)
+        .cloned()
+        .or_else(|| m.get(&name).and_then(super::fixed_value))
+        .map(|(_, length)| length as usize)
+}
+
+/// The arrays of known length all of whose elements are variables, with
+/// their element type and that length, e.g. `xs` and 3 for `xs[0]`, `xs[1]`
+/// and `xs[2]` when `xs.length == 3`.
+fn arrays(m: &HashMap<String, Variable>, fixed: &[FixedValue]) -> BTreeMap<String, (Type, usize)> {
+    lazy_static! {
+        static ref ELEMENT: Regex = Regex::new(r"^(.+)\[(\d+)\]$").unwrap();
+    }
+    let mut indices: BTreeMap<String, (Type, BTreeSet<usize>)> = BTreeMap::new();
+    let mut mixed = BTreeSet::new();
+    for (name, var) in m.iter() {
+        if let Some(cap) = ELEMENT.captures(name) {
+            if let Ok(index) = cap[2].parse() {
+                let &mut (ref typ, ref mut set) = indices
+                    .entry(String::from(&cap[1]))
+                    .or_insert_with(|| (var.typ.clone(), BTreeSet::new()));
+                if *typ != var.typ {
+                    mixed.insert(String::from(&cap[1]));
+                }
+                set.insert(index);
+            }
+        }
+    }
+    indices
+        .into_iter()
+        .filter(|&(ref base, (_, ref indices))| {
+            !mixed.contains(base) && indices.iter().enumerate().all(|(i, &index)| i == index)
+                && length(m, fixed, base) == Some(indices.len())
+        })
+        .map(|(base, (typ, indices))| (base, (typ, indices.len())))
+        .collect()
+}
+
+fn element(base: &str, index: usize) -> String {
+    format!("{}[{}]", base, index)
+}
+
+fn binomial(n: usize, k: usize) -> u128 {
+    (0..k).fold(1, |acc, i| acc * (n - i) as u128 / (i + 1) as u128)
+}
+
+/// The condition on the element `xs[i]` of a domain.
+fn element_condition(domain: &Domain, base: &str, typ: &Type) -> Result<String, Box<Error>> {
+    super::domain_to_string(domain, &format!("{}[i]", base), typ)
+}
+
+/// Whether the two paths are the same apart from the elements of `base`.
+fn same_besides(a: &(Conjunct, Vec<String>), b: &(Conjunct, Vec<String>), base: &str) -> bool {
+    let prefix = format!("{}[", base);
+    (a.0).1 == (b.0).1 && a.1 == b.1 && (a.0).0.len() == (b.0).0.len()
+        && (a.0).0.iter().all(|(name, var)| {
+            name.starts_with(&prefix) || (b.0).0.get(name).map_or(false, |other| var == other)
+        })
+}
+
+/// Replaces the paths that only differ in which `k` of the `n` elements of
+/// `base` lie in a domain, and in which the others lie in its complement, by
+/// a single path stating `for exactly k i, ..`. The array must have length
+/// `n` on these paths.
+fn quantify_exactly(
+    paths: Vec<(Conjunct, Vec<String>)>,
+    fixed: &[FixedValue],
+    base: &str,
+    typ: &Type,
+    n: usize,
+) -> Result<Vec<(Conjunct, Vec<String>)>, Box<Error>> {
+    let mut groups: Vec<Vec<(Conjunct, Vec<String>)>> = Vec::new();
+    for path in paths.into_iter() {
+        match groups.iter().position(|group| same_besides(&group[0], &path, base)) {
+            Some(i) => groups[i].push(path),
+            None => groups.push(vec![path]),
+        }
+    }
+    let mut quantified = Vec::new();
+    for group in groups.into_iter() {
+        let domains: Vec<Vec<&Domain>> = group
+            .iter()
+            .map(|path| (0..n).map(|i| &(path.0).0[&element(base, i)].range).collect())
+            .collect();
+        let mut distinct: Vec<&Domain> = Vec::new();
+        for &domain in domains.iter().flat_map(|d| d.iter()) {
+            if !distinct.contains(&domain) {
+                distinct.push(domain);
+            }
+        }
+        let exact = match (distinct.len(), (group[0].0).1.len()) {
+            (2, 0) => {
+                distinct[0].intersect(distinct[1]).is_empty()
+                    && distinct[0].exact_union(distinct[1]) == Some(typ.domain())
+            }
+            _ => false,
+        };
+        let patterns: BTreeSet<Vec<bool>> = domains
+            .iter()
+            .map(|d| d.iter().map(|&domain| domain == distinct[0]).collect())
+            .collect();
+        let k = domains[0].iter().filter(|&&domain| domain == distinct[0]).count();
+        let complete = exact && k > 0 && k < n && patterns.len() == group.len()
+            && length(&(group[0].0).0, fixed, base) == Some(n)
+            && binomial(n, k) == group.len() as u128
+            && patterns.iter().all(|p| p.iter().filter(|&&b| b).count() == k);
+        if !complete {
+            quantified.extend(group);
+            continue;
+        }
+        let (mut c, mut k) = (element_condition(distinct[0], base, typ)?, k);
+        let complement = element_condition(distinct[1], base, typ)?;
+        if complement.len() < c.len() {
+            c = complement;
+            k = n - k;
+        }
+        let (mut path, mut statements) = group.into_iter().next().unwrap();
+        for i in 0..n {
+            (path.0).remove(&element(base, i));
+        }
+        statements.push(format!("for exactly {} i, {}", k, c));
+        quantified.push((path, statements));
+    }
+    Ok(quantified)
+}
+
+/// Replaces the elements of `base` in the path by `for all i, ..` if the
+/// array has length `n` and its elements all lie in the same domain.
+fn quantify_all(
+    path: &mut (Conjunct, Vec<String>),
+    fixed: &[FixedValue],
+    base: &str,
+    typ: &Type,
+    n: usize,
+) -> Result<(), Box<Error>> {
+    let domain = match (path.0).0.get(&element(base, 0)) {
+        Some(var) => var.range.clone(),
+        None => return Ok(()),
+    };
+    if (path.0).1.len() != 0 || length(&(path.0).0, fixed, base) != Some(n)
+        || (1..n).any(|i| (path.0).0[&element(base, i)].range != domain)
+    {
+        return Ok(());
+    }
+    for i in 0..n {
+        (path.0).0.remove(&element(base, i));
+    }
+    if domain != typ.domain() {
+        path.1.push(format!("for all i, {}", element_condition(&domain, base, typ)?));
+    }
+    Ok(())
+}
+
+/// States the conditions the paths repeat for every element of an array once
+/// for all elements, e.g. `for all i, xs[i] >= 1`, or the number of elements
+/// satisfying them, e.g. `for exactly 3 i, xs[i] >= 1`. Every path must be a
+/// box over the same variables. Only arrays whose length is fixed, by `fixed`
+/// or by the paths, are quantified, since the elements of the paths need not
+/// be all elements otherwise. Returns the remaining paths along with the
+/// statements replacing their elements.
+pub fn quantify(
+    paths: Vec<Conjunct>,
+    fixed: &[FixedValue],
+) -> Result<Vec<(Conjunct, Vec<String>)>, Box<Error>> {
+    let arrays = match paths.first() {
+        Some(&(ref m, _)) => arrays(m, fixed),
+        None => BTreeMap::new(),
+    };
+    let mut paths: Vec<(Conjunct, Vec<String>)> =
+        paths.into_iter().map(|path| (path, Vec::new())).collect();
+    for (base, (typ, n)) in arrays.into_iter() {
+        if n < 2 {
+            continue;
+        }
+        paths = quantify_exactly(paths, fixed, &base, &typ, n)?;
+        for path in paths.iter_mut() {
+            quantify_all(path, fixed, &base, &typ, n)?;
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod test {
+    use super::quantify;
+    use super::super::expression::{Condition, Conjunct, Expression};
+
+    fn path(s: &str) -> Conjunct {
+        match Expression::from_str(s) {
+            Expression::Parsed(Condition::Conditions(m, relations)) => (m, relations),
+            _ => panic!("unexpected condition {}", s),
+        }
+    }
+
+    #[test]
+    fn test_quantify_all() {
+        let paths = vec![path(
+            "[L]declare 'n':sint32, 'xs[0]':sint32, 'xs[1]':sint32 in \
+             (('n' > 0) && (('xs[0]' > 0) && ('xs[1]' > 0)))",
+        )];
+        let quantified = quantify(paths, &[(String::from("xs.length"), 2)]).unwrap();
+        assert_eq!(1, quantified.len());
+        assert_eq!(vec!["for all i, xs[i] >= 1"], quantified[0].1);
+        assert_eq!(1, ((quantified[0].0).0).len());
+    }
+
+    #[test]
+    fn test_quantify_exactly() {
+        let declaration = "[L]declare 'xs[0]':sint32, 'xs[1]':sint32, 'xs[2]':sint32 in ";
+        let paths = vec![
+            path(&format!("{}(('xs[0]' > 0) && (('xs[1]' <= 0) && ('xs[2]' <= 0)))", declaration)),
+            path(&format!("{}(('xs[0]' <= 0) && (('xs[1]' > 0) && ('xs[2]' <= 0)))", declaration)),
+            path(&format!("{}(('xs[0]' <= 0) && (('xs[1]' <= 0) && ('xs[2]' > 0)))", declaration)),
+        ];
+        let fixed = [(String::from("xs.length"), 3)];
+        let quantified = quantify(paths.clone(), &fixed).unwrap();
+        assert_eq!(1, quantified.len());
+        assert_eq!(vec!["for exactly 1 i, xs[i] >= 1"], quantified[0].1);
+        // one of the combinations is missing
+        let quantified = quantify(paths[..2].to_vec(), &fixed).unwrap();
+        assert_eq!(2, quantified.len());
+        assert!(quantified.iter().all(|&(_, ref statements)| statements.len() == 0));
+    }
+
+    #[test]
+    fn test_quantify_unknown_length() {
+        let all = path(
+            "[L]declare 'xs.length':sint32, 'xs[0]':sint32, 'xs[1]':sint32 in \
+             (('xs.length' >= 2) && (('xs[0]' > 0) && ('xs[1]' > 0)))",
+        );
+        let declaration = "[L]declare 'xs[0]':sint32, 'xs[1]':sint32 in ";
+        let exactly = vec![
+            path(&format!("{}(('xs[0]' > 0) && ('xs[1]' <= 0))", declaration)),
+            path(&format!("{}(('xs[0]' <= 0) && ('xs[1]' > 0))", declaration)),
+        ];
+        // the elements beyond the first two are not constrained
+        let quantified = quantify(vec![all.clone()], &[]).unwrap();
+        assert_eq!(0, quantified[0].1.len());
+        let quantified = quantify(exactly.clone(), &[(String::from("xs.length"), 3)]).unwrap();
+        assert_eq!(2, quantified.len());
+        assert!(quantified.iter().all(|&(_, ref statements)| statements.len() == 0));
+        // the path fixes the length itself
+        let fixed = path(
+            "[L]declare 'xs.length':sint32, 'xs[0]':sint32, 'xs[1]':sint32 in \
+             (('xs.length' == 2) && (('xs[0]' > 0) && ('xs[1]' > 0)))",
+        );
+        let quantified = quantify(vec![fixed], &[]).unwrap();
+        assert_eq!(vec!["for all i, xs[i] >= 1"], quantified[0].1);
+    }
+}
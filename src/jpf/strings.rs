@@ -0,0 +1,207 @@
+use regex::Regex;
+use std::error::Error;
+use std::i32;
+
+use super::expression::{Domain, Type};
+use super::super::range::Range;
+
+/// The parameter of a driver stating whether the string `name` is null.
+pub fn null_parameter(name: &str) -> String {
+    format!("{}$null", name)
+}
+
+/// The parameter of a driver holding the characters of the string `name`.
+pub fn chars_parameter(name: &str) -> String {
+    format!("{}$chars", name)
+}
+
+/// The classes of characters, with the intervals of their values, described
+/// by name rather than by their bounds.
+static CLASSES: [(&str, &[(i128, i128)]); 5] = [
+    ("a digit", &[(48, 57)]),
+    ("an uppercase letter", &[(65, 90)]),
+    ("a lowercase letter", &[(97, 122)]),
+    ("a letter", &[(65, 90), (97, 122)]),
+    ("a letter or digit", &[(48, 57), (65, 90), (97, 122)]),
+];
+
+/// The intervals of the values of a type not in the given intervals.
+fn complement(intervals: &[(i128, i128)], typ: &Type) -> Vec<(i128, i128)> {
+    let range = typ.range();
+    let (lower, upper) = (range.get_ranges()[0].0, range.get_ranges()[0].1);
+    let mut complement = Vec::new();
+    let mut next = lower;
+    for &(l, u) in intervals.iter() {
+        if l > next {
+            complement.push((next, l - 1));
+        }
+        next = u + 1;
+    }
+    if next <= upper {
+        complement.push((next, upper));
+    }
+    complement
+}
+
+/// Refers to the strings a driver passes as arrays of characters in terms of
+/// the strings, e.g. `s.charAt(0)` for `s$chars[0]` and `s.length()` for
+/// `s$chars.length`.
+pub fn rename(s: &str) -> String {
+    lazy_static! {
+        static ref LENGTH: Regex = Regex::new(r"(\w+)\$chars\.length").unwrap();
+        static ref CHAR: Regex = Regex::new(r"(\w+)\$chars\[(\w+)\]").unwrap();
+    }
+    let s = LENGTH.replace_all(s, "${1}.length()");
+    let s = CHAR.replace_all(&s, "${1}.charAt(${2})");
+    String::from(s)
+}
+
+/// Refers to the string `name` in terms of the parameters a driver passes it
+/// as, e.g. `s$chars.length` for `s.length()` and `!s$null` for `s != null`,
+/// the inverse of `rename`.
+pub fn encode(s: &str, name: &str) -> String {
+    // the parameters as replacements, in which `$` must be doubled
+    let (null, chars) = (
+        null_parameter(name).replace('$', "$$"),
+        chars_parameter(name).replace('$', "$$"),
+    );
+    // the string itself rather than a field of the same name, e.g. `other.s`
+    let name = format!(r"(^|[^\w.$]){}", ::regex::escape(name));
+    let replacements = [
+        (format!(r"{}\s*!=\s*null\b", name), format!("!{}", null)),
+        (format!(r"{}\s*==\s*null\b", name), null),
+        (format!(r"{}\.length\(\)", name), format!("{}.length", chars)),
+        (format!(r"{}\.isEmpty\(\)", name), format!("{}.length == 0", chars)),
+        (format!(r"{}\.charAt\((\w+)\)", name), format!("{}[${{2}}]", chars)),
+    ];
+    let mut encoded = String::from(s);
+    for &(ref from, ref to) in replacements.iter() {
+        let re = Regex::new(from).unwrap();
+        encoded = String::from(re.replace_all(&encoded, format!("${{1}}{}", to).as_str()));
+    }
+    encoded
+}
+
+/// The condition on the string a variable of a driver encodes, e.g.
+/// `s.isEmpty()` or `s.charAt(0) is a digit`, or None if the variable does not
+/// encode a string.
+pub fn domain_to_string(
+    domain: &Domain,
+    name: &str,
+    typ: &Type,
+) -> Option<Result<String, Box<Error>>> {
+    let range = domain.as_integer()?;
+    if name.ends_with("$null") && *typ == Type::Bool {
+        let string = &name[..name.len() - "$null".len()];
+        return Some(Ok(match range.get_ranges().as_slice() {
+            [(1, 1)] => format!("{} == null", string),
+            [(0, 0)] => format!("{} != null", string),
+            _ => String::new(),
+        }));
+    }
+    if name.ends_with("$chars.length") {
+        let string = &name[..name.len() - "$chars.length".len()];
+        let length = range.intersect(&Range::from(0, i32::MAX as i128));
+        return Some(match length.get_ranges().as_slice() {
+            [(0, 0)] => Ok(format!("{}.isEmpty()", string)),
+            [(1, u)] if *u == i32::MAX as i128 => Ok(format!("!{}.isEmpty()", string)),
+            [(0, _), ..] if length.get_congruence().0 == 1 => {
+                // the length is not negative anyway
+                let length = length.union(&Range::from(i32::MIN as i128, -1));
+                super::ranges_to_string(&length, &format!("{}.length()", string), typ)
+            }
+            _ => super::ranges_to_string(&length, &format!("{}.length()", string), typ),
+        });
+    }
+    if name.contains("$chars[") && *typ == Type::UInt16 {
+        let name = rename(name);
+        if range.get_congruence().0 == 1 {
+            for &(class, intervals) in CLASSES.iter() {
+                if range.get_ranges().as_slice() == intervals {
+                    return Some(Ok(format!("{} is {}", name, class)));
+                }
+                if *range.get_ranges() == complement(intervals, typ) {
+                    return Some(Ok(format!("{} is not {}", name, class)));
+                }
+            }
+        }
+        return Some(super::ranges_to_string(range, &name, typ));
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{domain_to_string, encode, rename};
+    use super::super::expression::{Domain, Type};
+    use super::super::super::range::Range;
+
+    fn describe(range: Range, name: &str, typ: Type) -> String {
+        domain_to_string(&Domain::Integer(range), name, &typ)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_domain_to_string() {
+        assert_eq!("s == null", describe(Range::from(1, 1), "s$null", Type::Bool));
+        assert_eq!(
+            "s.isEmpty()",
+            describe(Range::from(0, 0), "s$chars.length", Type::SInt32)
+        );
+        assert_eq!(
+            "!s.isEmpty()",
+            describe(Range::from(1, i32::max_value() as i128), "s$chars.length", Type::SInt32)
+        );
+        assert_eq!(
+            "s.length() <= 4",
+            describe(Range::from(0, 4), "s$chars.length", Type::SInt32)
+        );
+        assert_eq!(
+            "s.charAt(i) is a digit",
+            describe(Range::from(48, 57), "s$chars[i]", Type::UInt16)
+        );
+        assert_eq!(
+            "s.charAt(0) is not a lowercase letter",
+            describe(
+                Range::from(0, 96).union(&Range::from(123, 65535)),
+                "s$chars[0]",
+                Type::UInt16
+            )
+        );
+        assert_eq!(
+            "s.charAt(1) == 'x'",
+            describe(Range::from(120, 120), "s$chars[1]", Type::UInt16)
+        );
+        assert_eq!(
+            "s.length() < 3 || s.length() > 5",
+            describe(
+                Range::from(0, 2).union(&Range::from(6, i32::max_value() as i128)),
+                "s$chars.length",
+                Type::SInt32
+            )
+        );
+        let domain = Domain::Integer(Range::from(0, 0));
+        assert!(domain_to_string(&domain, "n", &Type::SInt32).is_none());
+    }
+
+    #[test]
+    fn test_rename() {
+        assert_eq!(
+            "s.charAt(0) == s.charAt(1) for s.length() == 2",
+            rename("s$chars[0] == s$chars[1] for s$chars.length == 2")
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(
+            "!s$null && s$chars.length <= 4 && s$chars[0] == t.charAt(0) && s$chars.length == 0",
+            encode("s != null && s.length() <= 4 && s.charAt(0) == t.charAt(0) && s.isEmpty()", "s")
+        );
+        assert_eq!(
+            "s$null || other.s.length() > 0",
+            encode("s == null || other.s.length() > 0", "s")
+        );
+    }
+}
@@ -11,26 +11,112 @@ extern crate serde_derive;
 extern crate serde_json as json;
 
 pub mod daikon;
+pub mod dbm;
+pub mod float_range;
 pub mod ftw;
 pub mod jpf;
 pub mod range;
 
 use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
 use std::fmt;
 use std::fs::{self, File};
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use rand::Rng;
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     jpf_home: String,
     jvm_flags: String,
     classpath: Vec<String>,
     daikon_classpath: Vec<String>,
     max_depth: u32,
+    /// whether to show example calls of the method in the documentation
+    #[serde(default)]
+    examples: bool,
+    /// if set, the number of seconds for rerunning JPF with increasing depths
+    /// until the results stop changing
+    #[serde(default)]
+    time_budget: Option<u64>,
+    /// options of JDart for every method
+    #[serde(default)]
+    jdart: JdartOptions,
+    /// options of JDart for single methods, keyed by the name of the method,
+    /// which take precedence over `jdart` apart from the constraints, which
+    /// add to the ones of `jdart`
+    #[serde(default)]
+    methods: HashMap<String, JdartOptions>,
+}
+
+/// Options of the JDart run, each replacing the default when set.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JdartOptions {
+    /// the constraint solver, i.e. the value of `symbolic.dp`
+    solver: Option<String>,
+    /// whether the solver reasons about bit vectors rather than integers
+    bitvectors: Option<bool>,
+    /// patterns of the fields that are symbolic
+    symbolic_include: Option<Vec<String>>,
+    /// patterns of the fields that are not symbolic
+    symbolic_exclude: Option<Vec<String>>,
+    /// the classes whose static fields are symbolic
+    symbolic_statics: Option<Vec<String>>,
+    max_depth: Option<u32>,
+    /// conditions on the inputs JDart may assume, as Java, e.g. `xs.length > 0`
+    constraints: Option<Vec<String>>,
+    /// the largest length of the array parameters JDart explores
+    max_array_length: Option<u32>,
+    /// if set, the largest length of the string parameters JDart explores,
+    /// which a generated driver builds from arrays of characters
+    max_string_length: Option<u32>,
+    /// whether to also assume the constraints the `@param` tags of the Javadoc
+    /// of the method state, taken from its source next to the classes
+    constraints_from_javadoc: Option<bool>,
+    /// further JPF properties, e.g. `"search.multiple_errors": "true"`
+    #[serde(default)]
+    properties: BTreeMap<String, String>,
+}
+
+impl JdartOptions {
+    /// These options with the ones set in `other` taking precedence, and the
+    /// constraints of both.
+    fn overridden_by(&self, other: &JdartOptions) -> JdartOptions {
+        let mut properties = self.properties.clone();
+        properties.extend(other.properties.clone());
+        JdartOptions {
+            solver: other.solver.clone().or_else(|| self.solver.clone()),
+            bitvectors: other.bitvectors.or(self.bitvectors),
+            symbolic_include: other
+                .symbolic_include
+                .clone()
+                .or_else(|| self.symbolic_include.clone()),
+            symbolic_exclude: other
+                .symbolic_exclude
+                .clone()
+                .or_else(|| self.symbolic_exclude.clone()),
+            symbolic_statics: other
+                .symbolic_statics
+                .clone()
+                .or_else(|| self.symbolic_statics.clone()),
+            max_depth: other.max_depth.or(self.max_depth),
+            constraints: match (&self.constraints, &other.constraints) {
+                (&Some(ref a), &Some(ref b)) => Some(a.iter().chain(b.iter()).cloned().collect()),
+                (a, b) => b.clone().or_else(|| a.clone()),
+            },
+            max_array_length: other.max_array_length.or(self.max_array_length),
+            max_string_length: other.max_string_length.or(self.max_string_length),
+            constraints_from_javadoc: other
+                .constraints_from_javadoc
+                .or(self.constraints_from_javadoc),
+            properties,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -87,6 +173,35 @@ impl Config {
     pub fn from_str(s: &str) -> Result<Config, json::Error> {
         json::from_str(s)
     }
+
+    /// Adds conditions on the inputs JDart may assume for every method.
+    pub fn add_constraints(&mut self, constraints: &[String]) {
+        self.jdart
+            .constraints
+            .get_or_insert_with(Vec::new)
+            .extend(constraints.iter().cloned());
+    }
+
+    /// Adds a directory to the classpath of JPF.
+    pub fn add_classpath(&mut self, path: &str) {
+        self.classpath.push(String::from(path));
+    }
+
+    /// The options of JDart for the method with the given name.
+    fn jdart_options(&self, method_name: &str) -> JdartOptions {
+        match self.methods.get(method_name) {
+            Some(options) => self.jdart.overridden_by(options),
+            None => self.jdart.clone(),
+        }
+    }
+
+    pub fn show_examples(&self) -> bool {
+        self.examples
+    }
+
+    pub fn time_budget(&self) -> Option<Duration> {
+        self.time_budget.map(Duration::from_secs)
+    }
 }
 
 fn random_alphanumeric_string(size: usize) -> String {
@@ -161,7 +276,7 @@ pub fn construct_path(parent: &PathBuf, addition: &str) -> Result<String, Box<Er
 
 #[cfg(test)]
 mod test {
-    use super::parse_java_method;
+    use super::{parse_java_method, Config};
 
     #[test]
     fn test_parse_nullary_method() {
@@ -174,4 +289,58 @@ mod test {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_jdart_options() {
+        let mut config = Config::from_str(
+            r#"{
+                "jpf_home": "jpf",
+                "jvm_flags": "",
+                "classpath": [],
+                "daikon_classpath": [],
+                "max_depth": 42,
+                "jdart": {
+                    "solver": "z3",
+                    "bitvectors": true,
+                    "constraints": ["n >= 0"],
+                    "properties": {"a": "1", "b": "2"}
+                },
+                "methods": {
+                    "count": {
+                        "solver": "coral",
+                        "max_depth": 7,
+                        "constraints": ["xs.length > 0"],
+                        "properties": {"b": "3"}
+                    }
+                }
+            }"#,
+        ).unwrap();
+        config.add_constraints(&[String::from("m < 10")]);
+        let options = config.jdart_options("count");
+        assert_eq!(Some(String::from("coral")), options.solver);
+        assert_eq!(Some(true), options.bitvectors);
+        assert_eq!(Some(7), options.max_depth);
+        assert_eq!(
+            Some(vec![
+                String::from("n >= 0"),
+                String::from("m < 10"),
+                String::from("xs.length > 0"),
+            ]),
+            options.constraints
+        );
+        assert_eq!(
+            vec![
+                (String::from("a"), String::from("1")),
+                (String::from("b"), String::from("3")),
+            ],
+            options.properties.into_iter().collect::<Vec<(String, String)>>()
+        );
+        let options = config.jdart_options("other");
+        assert_eq!(Some(String::from("z3")), options.solver);
+        assert_eq!(None, options.max_depth);
+        assert_eq!(
+            Some(vec![String::from("n >= 0"), String::from("m < 10")]),
+            options.constraints
+        );
+    }
 }
@@ -1,37 +1,237 @@
-use std::cmp;
+use std::{cmp, i128};
+
+/// Upper bound on the number of values a range with a congruence is expanded
+/// into when the congruence cannot be kept.
+const MAX_EXPANDED_VALUES: i128 = 1024;
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct Range {
     /// ranges of the value, each represented as a lower bound and an upper
-    /// bound, both inclusive.
-    ranges: Vec<(i64, i64)>,
+    /// bound, both inclusive and both satisfying the congruence.
+    ranges: Vec<(i128, i128)>,
+    /// the values are congruent to `remainder` modulo `modulus`, where
+    /// 0 <= remainder < modulus. A modulus of 1 does not restrict the values.
+    modulus: i128,
+    remainder: i128,
+    /// whether the range may contain values it should not, as a union or a
+    /// difference could not be represented exactly
+    approximate: bool,
 }
 
 impl Range {
-    pub fn from(lower: i64, upper: i64) -> Range {
-        Range {
-            ranges: if lower <= upper {
-                vec![(lower, upper)]
-            } else {
-                vec![]
-            },
+    pub fn from(lower: i128, upper: i128) -> Range {
+        Range::with_congruence(vec![(lower, upper)], 1, 0)
+    }
+
+    /// All values congruent to `remainder` modulo `modulus`.
+    pub fn congruent(modulus: i128, remainder: i128) -> Range {
+        assert!(modulus > 0);
+        Range::with_congruence(
+            vec![(i128::MIN, i128::MAX)],
+            modulus,
+            remainder.rem_euclid(modulus),
+        )
+    }
+
+    /// Shrinks the bounds of `ranges` to values satisfying the congruence and
+    /// drops the ranges left empty. Ranges that only consist of single values
+    /// do not need a congruence.
+    fn with_congruence(ranges: Vec<(i128, i128)>, modulus: i128, remainder: i128) -> Range {
+        let mut aligned: Vec<(i128, i128)> = Vec::with_capacity(ranges.len());
+        for (l, u) in ranges.into_iter() {
+            let l = l.checked_add((remainder - l.rem_euclid(modulus)).rem_euclid(modulus));
+            let u = u.checked_sub((u.rem_euclid(modulus) - remainder).rem_euclid(modulus));
+            let (l, u) = match (l, u) {
+                (Some(l), Some(u)) if l <= u => (l, u),
+                _ => continue,
+            };
+            if let Some(last) = aligned.last_mut() {
+                // no value of the congruence lies between the two ranges
+                if l.checked_sub(last.1).map_or(false, |gap| gap <= modulus) {
+                    last.1 = u;
+                    continue;
+                }
+            }
+            aligned.push((l, u));
         }
+        if aligned.iter().all(|&(l, u)| l == u) {
+            Range {
+                ranges: aligned,
+                modulus: 1,
+                remainder: 0,
+                approximate: false,
+            }
+        } else {
+            Range {
+                ranges: aligned,
+                modulus,
+                remainder,
+                approximate: false,
+            }
+        }
+    }
+
+    /// Marks the range as approximate if `approximate` is set.
+    fn approximated(mut self, approximate: bool) -> Range {
+        self.approximate |= approximate;
+        self
     }
 
-    pub fn get_ranges(&self) -> &Vec<(i64, i64)> {
+    pub fn get_ranges(&self) -> &Vec<(i128, i128)> {
         &self.ranges
     }
 
+    /// The modulus and the remainder of the congruence of the values.
+    pub fn get_congruence(&self) -> (i128, i128) {
+        (self.modulus, self.remainder)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.len() == 0
+    }
+
+    /// Whether the range may contain values it should not, see `union` and
+    /// `difference`.
+    pub fn is_approximate(&self) -> bool {
+        self.approximate
+    }
+
+    /// The value closest to 0, preferring the positive one, or None if the
+    /// range is empty.
+    pub fn example(&self) -> Option<i128> {
+        let (modulus, remainder) = (self.modulus, self.remainder);
+        self.ranges
+            .iter()
+            .flat_map(|&(l, u)| {
+                // the values nearest to 0 on either side of it
+                vec![l, u, remainder, remainder - modulus]
+                    .into_iter()
+                    .filter(move |&v| l <= v && v <= u)
+            })
+            .min_by_key(|&v| (v.checked_abs().unwrap_or(i128::MAX), v < 0))
+    }
+
+    /// The values as ranges without a congruence, or None if there are more
+    /// than `MAX_EXPANDED_VALUES` of them.
+    fn expanded(&self) -> Option<Vec<(i128, i128)>> {
+        if self.modulus == 1 {
+            return Some(self.ranges.clone());
+        }
+        let mut count = 0;
+        for &(l, u) in self.ranges.iter() {
+            count += u.checked_sub(l)? / self.modulus + 1;
+            if count > MAX_EXPANDED_VALUES {
+                return None;
+            }
+        }
+        let modulus = self.modulus;
+        Some(self.ranges
+            .iter()
+            .flat_map(|&(l, u)| {
+                (0..(u - l) / modulus + 1).map(move |i| (l + i * modulus, l + i * modulus))
+            })
+            .collect())
+    }
+
+    /// Whether `other` consists of single values satisfying the congruence.
+    fn covers(&self, other: &Range) -> bool {
+        other.modulus == 1 && other.ranges.iter().all(|&(l, u)| {
+            l == u && l.rem_euclid(self.modulus) == self.remainder
+        })
+    }
+
+    /// The values of this range that are not in `other`. This
+    /// over-approximates, and marks the result as approximate, if the
+    /// congruences differ and there are too many common values to enumerate.
     pub fn difference(&self, other: &Range) -> Range {
-        let self_len = self.ranges.len();
-        let other_len = other.ranges.len();
+        let approximate = self.approximate || other.approximate;
+        // every value of self satisfies the congruence of other
+        if self.modulus % other.modulus == 0 && self.remainder % other.modulus == other.remainder {
+            return Range::with_congruence(
+                Range::difference_intervals(&self.ranges, &other.ranges),
+                self.modulus,
+                self.remainder,
+            ).approximated(approximate);
+        }
+        match self.intersect(other).expanded() {
+            Some(common) => Range::with_congruence(
+                Range::difference_intervals(&self.ranges, &common),
+                self.modulus,
+                self.remainder,
+            ).approximated(approximate),
+            None => self.clone().approximated(true),
+        }
+    }
+
+    pub fn intersect(&self, other: &Range) -> Range {
+        let ranges = Range::intersect_intervals(&self.ranges, &other.ranges);
+        let congruence = combine_congruences(self.get_congruence(), other.get_congruence());
+        let intersection = match congruence {
+            Some(Some((modulus, remainder))) => Range::with_congruence(ranges, modulus, remainder),
+            Some(None) => Range::from(1, 0),
+            // the combined modulus does not fit, keep the larger one
+            None => if self.modulus > other.modulus {
+                Range::with_congruence(ranges, self.modulus, self.remainder).approximated(true)
+            } else {
+                Range::with_congruence(ranges, other.modulus, other.remainder).approximated(true)
+            },
+        };
+        intersection.approximated(self.approximate || other.approximate)
+    }
+
+    /// The values in either range, or None if they cannot be represented,
+    /// i.e. if the congruences differ and there are too many values to
+    /// enumerate.
+    pub fn exact_union(&self, other: &Range) -> Option<Range> {
+        let approximate = self.approximate || other.approximate;
+        if self.is_empty() {
+            return Some(other.clone().approximated(approximate));
+        }
+        if other.is_empty() {
+            return Some(self.clone().approximated(approximate));
+        }
+        let ranges = Range::union_intervals(&self.ranges, &other.ranges);
+        if self.get_congruence() == other.get_congruence() || self.covers(other) {
+            return Some(
+                Range::with_congruence(ranges, self.modulus, self.remainder)
+                    .approximated(approximate),
+            );
+        }
+        if other.covers(self) {
+            return Some(
+                Range::with_congruence(ranges, other.modulus, other.remainder)
+                    .approximated(approximate),
+            );
+        }
+        let ranges = Range::union_intervals(&self.expanded()?, &other.expanded()?);
+        Some(Range::with_congruence(ranges, 1, 0).approximated(approximate))
+    }
+
+    /// The values in either range. If they cannot be represented exactly, the
+    /// congruence is weakened to one satisfied by both ranges and the result
+    /// is marked as approximate.
+    pub fn union(&self, other: &Range) -> Range {
+        self.exact_union(other).unwrap_or_else(|| {
+            let modulus = gcd(
+                gcd(self.modulus, other.modulus),
+                self.remainder - other.remainder,
+            );
+            Range::with_congruence(
+                Range::union_intervals(&self.ranges, &other.ranges),
+                modulus,
+                self.remainder % modulus,
+            ).approximated(true)
+        })
+    }
+
+    fn difference_intervals(this: &[(i128, i128)], other: &[(i128, i128)]) -> Vec<(i128, i128)> {
+        let self_len = this.len();
+        let other_len = other.len();
         if self_len == 0 {
-            return Range { ranges: Vec::new() };
+            return Vec::new();
         }
         if other_len == 0 {
-            return Range {
-                ranges: self.ranges.clone(),
-            };
+            return this.to_vec();
         }
         let mut ranges = Vec::with_capacity(cmp::max(self_len, other_len));
         let mut a = 0;
@@ -44,14 +244,14 @@ impl Range {
         {
             if update_a_b {
                 unsafe {
-                    let &(l, u) = self.ranges.get_unchecked(next_self_index);
+                    let &(l, u) = this.get_unchecked(next_self_index);
                     a = l;
                     b = u;
                 }
                 next_self_index += 1;
                 update_a_b = false;
             }
-            let &(c, d) = unsafe { other.ranges.get_unchecked(other_index) };
+            let &(c, d) = unsafe { other.get_unchecked(other_index) };
             if a > d {
                 //       a  b
                 // ------+--+-
@@ -63,8 +263,13 @@ impl Range {
                 // ----+--+-
                 //  c  d
                 // -+--+----
-                a = d + 1;
-                update_a_b = a > b;
+                match d.checked_add(1) {
+                    Some(next) => {
+                        a = next;
+                        update_a_b = a > b;
+                    }
+                    None => update_a_b = true,
+                }
             } else if a >= c && b > d {
                 //    a  b
                 // ---+--+-
@@ -84,11 +289,17 @@ impl Range {
                 // -+-----+-    -+----+--
                 //    c  d   OR    c  d
                 // ---+--+--    ---+--+--
-                if a <= c - 1 {
+                if a < c {
                     ranges.push((a, c - 1));
                 }
-                a = d + 1;
-                update_a_b = a > b;
+                match d.checked_add(1) {
+                    Some(next) => {
+                        a = next;
+                        update_a_b = a > b;
+                    }
+                    // b == d == i128::MAX, nothing is left of [a, b]
+                    None => update_a_b = true,
+                }
                 other_index += 1;
             } else if b < c {
                 //  a  b
@@ -102,7 +313,7 @@ impl Range {
                 // -+--+---
                 //    c  d
                 // ---+--+-
-                if a <= c - 1 {
+                if a < c {
                     ranges.push((a, c - 1));
                 }
                 update_a_b = true;
@@ -112,23 +323,23 @@ impl Range {
             ranges.push((a, b));
         }
         if next_self_index < self_len {
-            ranges.extend(self.ranges[next_self_index..].iter());
+            ranges.extend(this[next_self_index..].iter());
         }
-        Range { ranges }
+        ranges
     }
 
-    pub fn intersect(&self, other: &Range) -> Range {
-        let self_len = self.ranges.len();
-        let other_len = other.ranges.len();
+    fn intersect_intervals(this: &[(i128, i128)], other: &[(i128, i128)]) -> Vec<(i128, i128)> {
+        let self_len = this.len();
+        let other_len = other.len();
         if self_len == 0 || other_len == 0 {
-            return Range { ranges: Vec::new() };
+            return Vec::new();
         }
         let mut ranges = Vec::with_capacity(cmp::max(self_len, other_len));
         let mut self_index = 0;
         let mut other_index = 0;
         while self_index < self_len && other_index < other_len {
-            let &(a, b) = unsafe { self.ranges.get_unchecked(self_index) };
-            let &(c, d) = unsafe { other.ranges.get_unchecked(other_index) };
+            let &(a, b) = unsafe { this.get_unchecked(self_index) };
+            let &(c, d) = unsafe { other.get_unchecked(other_index) };
             if a > d {
                 //       a  b
                 // ------+--+-
@@ -171,28 +382,24 @@ impl Range {
                 self_index += 1;
             }
         }
-        Range { ranges }
+        ranges
     }
 
-    pub fn union(&self, other: &Range) -> Range {
-        let self_len = self.ranges.len();
-        let other_len = other.ranges.len();
+    fn union_intervals(this: &[(i128, i128)], other: &[(i128, i128)]) -> Vec<(i128, i128)> {
+        let self_len = this.len();
+        let other_len = other.len();
         if self_len == 0 {
-            return Range {
-                ranges: other.ranges.clone(),
-            };
+            return other.to_vec();
         }
         if other_len == 0 {
-            return Range {
-                ranges: self.ranges.clone(),
-            };
+            return this.to_vec();
         }
         let mut ranges = Vec::with_capacity(self_len + other_len);
         let mut self_index = 0;
         let mut other_index = 0;
         while self_index < self_len && other_index < other_len {
-            let &(a, b) = unsafe { self.ranges.get_unchecked(self_index) };
-            let &(c, d) = unsafe { other.ranges.get_unchecked(other_index) };
+            let &(a, b) = unsafe { this.get_unchecked(self_index) };
+            let &(c, d) = unsafe { other.get_unchecked(other_index) };
             if a <= c {
                 ranges.push((a, b));
                 self_index += 1;
@@ -201,15 +408,16 @@ impl Range {
                 other_index += 1;
             }
         }
-        ranges.extend(self.ranges[self_index..].iter());
-        ranges.extend(other.ranges[other_index..].iter());
+        ranges.extend(this[self_index..].iter());
+        ranges.extend(other[other_index..].iter());
         // simplify
         assert!(ranges.len() >= 2);
         let &(mut a, mut b) = unsafe { ranges.get_unchecked(0) };
         let mut write_head = 0;
         for read_head in 1..ranges.len() {
             let &(c, d) = unsafe { ranges.get_unchecked(read_head) };
-            if b < c - 1 {
+            // [a, b] and [c, d] are neither overlapping nor adjacent
+            if b.checked_add(1).map_or(false, |next| next < c) {
                 unsafe {
                     *ranges.get_unchecked_mut(write_head) = (a, b);
                 }
@@ -224,20 +432,76 @@ impl Range {
             *ranges.get_unchecked_mut(write_head) = (a, b);
         }
         ranges.truncate(write_head + 1);
-        Range { ranges }
+        ranges
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The inverse of `a` modulo `m`, where `a` and `m` are coprime.
+fn inverse(a: i128, m: i128) -> i128 {
+    let (mut old_r, mut r) = (a, m);
+    let (mut old_s, mut s) = (1, 0);
+    while r != 0 {
+        let q = old_r / r;
+        let next_r = old_r - q * r;
+        old_r = r;
+        r = next_r;
+        let next_s = old_s - q * s;
+        old_s = s;
+        s = next_s;
+    }
+    old_s.rem_euclid(m)
+}
+
+/// The congruence satisfied by the values satisfying both congruences, using
+/// the Chinese remainder theorem. Returns Some(None) if no value satisfies
+/// both, and None if the combined modulus does not fit into an i128.
+fn combine_congruences(
+    (m1, r1): (i128, i128),
+    (m2, r2): (i128, i128),
+) -> Option<Option<(i128, i128)>> {
+    let g = gcd(m1, m2);
+    if (r2 - r1) % g != 0 {
+        return Some(None);
     }
+    // r1 + m1 * t is congruent to r2 modulo m2
+    let m = m2 / g;
+    let t = ((r2 - r1) / g)
+        .rem_euclid(m)
+        .checked_mul(inverse((m1 / g).rem_euclid(m), m))?
+        % m;
+    let modulus = m1.checked_mul(m)?;
+    Some(Some((modulus, r1 + m1 * t)))
 }
 
 #[cfg(test)]
 mod test {
     use super::Range;
+    use std::i128;
+
+    #[test]
+    fn test_example() {
+        assert_eq!(Some(0), Range::from(-5, 5).example());
+        assert_eq!(Some(-3), Range::from(i128::MIN, -3).example());
+        assert_eq!(Some(7), Range::from(-9, -8).union(&Range::from(7, 8)).example());
+        let odd = Range::congruent(2, 1).intersect(&Range::from(-10, 10));
+        assert_eq!(Some(1), odd.example());
+        assert_eq!(None, Range::from(1, 0).example());
+    }
 
     #[test]
     fn test_empty_difference() {
         let r1 = Range::from(3, 1); // empty range
         let r2 = Range::from(4, 6);
         let test1 = r1.difference(&r2);
-        assert_eq!(Vec::<(i64, i64)>::new(), test1.ranges);
+        assert_eq!(Vec::<(i128, i128)>::new(), test1.ranges);
         let test2 = r2.difference(&r1);
         assert_eq!(vec![(4, 6)], test2.ranges);
     }
@@ -271,7 +535,7 @@ mod test {
         let test1 = r1.difference(&r2);
         assert_eq!(vec![(1, 1), (5, 5), (8, 8), (12, 12)], test1.ranges);
         let test2 = r2.difference(&r1);
-        assert_eq!(Vec::<(i64, i64)>::new(), test2.ranges);
+        assert_eq!(Vec::<(i128, i128)>::new(), test2.ranges);
     }
 
     #[test]
@@ -281,7 +545,7 @@ mod test {
         let test1 = r1.difference(&r2);
         assert_eq!(vec![(1, 2), (5, 6)], test1.ranges);
         let test2 = r2.difference(&r1);
-        assert_eq!(Vec::<(i64, i64)>::new(), test2.ranges);
+        assert_eq!(Vec::<(i128, i128)>::new(), test2.ranges);
     }
 
     #[test]
@@ -361,9 +625,9 @@ mod test {
         let r1 = Range::from(3, 1); // empty range
         let r2 = Range::from(4, 6);
         let test1 = r1.intersect(&r2);
-        assert_eq!(Vec::<(i64, i64)>::new(), test1.ranges);
+        assert_eq!(Vec::<(i128, i128)>::new(), test1.ranges);
         let test2 = r2.intersect(&r1);
-        assert_eq!(Vec::<(i64, i64)>::new(), test2.ranges);
+        assert_eq!(Vec::<(i128, i128)>::new(), test2.ranges);
     }
 
     #[test]
@@ -371,9 +635,9 @@ mod test {
         let r1 = Range::from(1, 3);
         let r2 = Range::from(5, 6);
         let test1 = r1.intersect(&r2);
-        assert_eq!(Vec::<(i64, i64)>::new(), test1.ranges);
+        assert_eq!(Vec::<(i128, i128)>::new(), test1.ranges);
         let test2 = r2.intersect(&r1);
-        assert_eq!(Vec::<(i64, i64)>::new(), test2.ranges);
+        assert_eq!(Vec::<(i128, i128)>::new(), test2.ranges);
     }
 
     #[test]
@@ -417,4 +681,98 @@ mod test {
         let test2 = r2.intersect(&r1);
         assert_eq!(vec![(3, 4), (7, 7)], test2.ranges);
     }
+
+    #[test]
+    fn test_extreme_difference() {
+        let full = Range::from(i128::MIN, i128::MAX);
+        let test1 = full.difference(&Range::from(i128::MIN, i128::MIN));
+        assert_eq!(vec![(i128::MIN + 1, i128::MAX)], test1.ranges);
+        let test2 = full.difference(&Range::from(i128::MAX, i128::MAX));
+        assert_eq!(vec![(i128::MIN, i128::MAX - 1)], test2.ranges);
+        let test3 = full.difference(&Range::from(0, i128::MAX));
+        assert_eq!(vec![(i128::MIN, -1)], test3.ranges);
+        let test4 = Range::from(i128::MAX - 1, i128::MAX)
+            .difference(&Range::from(i128::MAX - 1, i128::MAX - 1));
+        assert_eq!(vec![(i128::MAX, i128::MAX)], test4.ranges);
+        let test5 = full.difference(&full);
+        assert_eq!(Vec::<(i128, i128)>::new(), test5.ranges);
+        let max = Range::from(i128::MAX, i128::MAX);
+        let test6 = max.difference(&Range::from(i128::MAX, i128::MAX));
+        assert_eq!(Vec::<(i128, i128)>::new(), test6.ranges);
+    }
+
+    #[test]
+    fn test_extreme_union() {
+        let test1 = Range::from(i128::MIN, 5).union(&Range::from(6, i128::MAX));
+        assert_eq!(vec![(i128::MIN, i128::MAX)], test1.ranges);
+        let test2 = Range::from(i128::MAX, i128::MAX).union(&Range::from(0, i128::MAX - 1));
+        assert_eq!(vec![(0, i128::MAX)], test2.ranges);
+        let test3 = Range::from(i128::MIN, i128::MIN).union(&Range::from(i128::MAX, i128::MAX));
+        assert_eq!(vec![(i128::MIN, i128::MIN), (i128::MAX, i128::MAX)], test3.ranges);
+    }
+
+    #[test]
+    fn test_congruent_intersection() {
+        let even = Range::congruent(2, 0).intersect(&Range::from(-3, 9));
+        assert_eq!(vec![(-2, 8)], even.ranges);
+        assert_eq!((2, 0), even.get_congruence());
+        let test = even.intersect(&Range::congruent(3, 1));
+        assert_eq!(vec![(-2, 4)], test.ranges);
+        assert_eq!((6, 4), test.get_congruence());
+        assert!(even.intersect(&Range::congruent(4, 1)).is_empty());
+        // a single value needs no congruence
+        assert_eq!(Range::from(4, 4), even.intersect(&Range::from(3, 5)));
+    }
+
+    #[test]
+    fn test_congruent_union() {
+        let r1 = Range::congruent(4, 0).intersect(&Range::from(0, 8));
+        let r2 = Range::congruent(4, 0).intersect(&Range::from(12, 20));
+        let test = r1.union(&r2);
+        assert_eq!(vec![(0, 20)], test.ranges);
+        assert_eq!((4, 0), test.get_congruence());
+        let test = r1.union(&Range::from(24, 24));
+        assert_eq!(vec![(0, 8), (24, 24)], test.ranges);
+        assert_eq!((4, 0), test.get_congruence());
+        let test = r1.union(&Range::from(1, 1));
+        assert_eq!(vec![(0, 1), (4, 4), (8, 8)], test.ranges);
+        assert_eq!((1, 0), test.get_congruence());
+        let odd = Range::congruent(2, 1);
+        assert_eq!(None, Range::congruent(4, 0).exact_union(&odd));
+        let test = Range::congruent(4, 0).union(&odd);
+        assert_eq!(vec![(i128::MIN, i128::MAX)], test.ranges);
+        assert!(test.is_approximate());
+        assert!(!r1.union(&r2).is_approximate());
+        // the approximation carries over to further operations
+        assert!(test.intersect(&Range::from(0, 10)).is_approximate());
+    }
+
+    #[test]
+    fn test_congruent_intersection_overflow() {
+        let (m1, m2) = (1i128 << 100, (1i128 << 100) + 1);
+        let test = Range::congruent(m1, 0).intersect(&Range::congruent(m2, 0));
+        // the combined modulus does not fit, so the larger one is kept
+        assert_eq!((m2, 0), test.get_congruence());
+        assert!(test.is_approximate());
+        assert!(!Range::congruent(4, 0).intersect(&Range::congruent(6, 0)).is_approximate());
+    }
+
+    #[test]
+    fn test_congruent_difference() {
+        let r1 = Range::congruent(2, 0).intersect(&Range::from(0, 20));
+        let test = r1.difference(&Range::from(5, 15));
+        assert_eq!(vec![(0, 4), (16, 20)], test.ranges);
+        assert_eq!((2, 0), test.get_congruence());
+        let test = r1.difference(&Range::congruent(4, 0));
+        assert_eq!(
+            vec![(2, 2), (6, 6), (10, 10), (14, 14), (18, 18)],
+            test.ranges
+        );
+        assert_eq!(r1, r1.difference(&Range::congruent(2, 1)));
+        assert!(!test.is_approximate());
+        // too many common values to enumerate
+        let test = Range::congruent(2, 0).difference(&Range::congruent(3, 0));
+        assert_eq!(Range::congruent(2, 0).ranges, test.ranges);
+        assert!(test.is_approximate());
+    }
 }